@@ -0,0 +1,66 @@
+use std::{
+    env, fs,
+    io::Result,
+    path::{Path, PathBuf},
+};
+
+/// Directories read by the webapp at runtime, embedded so that the binary can be deployed alone
+const EMBEDDED_DIRS: &[&str] = &[
+    "src/views",
+    "src/styles",
+    "src/assets",
+    "src/scripts",
+    "data/dictionaries",
+];
+
+fn main() -> Result<()> {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+
+    let mut files = vec![];
+    for dir in EMBEDDED_DIRS {
+        println!("cargo:rerun-if-changed={dir}");
+        find_files(&manifest_dir.join(dir), &mut files)?;
+    }
+    files.sort();
+
+    let mut generated = String::from("&[\n");
+    for path in files {
+        let relative_path = path
+            .strip_prefix(&manifest_dir)
+            .unwrap()
+            .to_string_lossy()
+            .replace('\\', "/");
+        generated.push_str(&format!(
+            "    ({relative_path:?}, include_bytes!({:?})),\n",
+            path.to_string_lossy()
+        ));
+    }
+    generated.push_str("]\n");
+
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("embedded_files.rs");
+    fs::write(out_path, generated)
+}
+
+fn find_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if is_hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            find_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
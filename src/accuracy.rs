@@ -0,0 +1,108 @@
+use anyhow::Result;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    models::{picture::Picture, user::User},
+    utils::{self, Day},
+};
+
+/// Guesses at most this many minutes away from the real time count as close
+pub const CLOSE_GUESS_MINUTES: u32 = 15;
+
+#[derive(Debug, Serialize, Default, ToSchema)]
+pub struct BestGuess {
+    #[schema(value_type = u32)]
+    pub day: Day,
+    pub error_minutes: u32,
+}
+
+/// How close the guesses of a player were to the real times, independently of the scoring curve
+#[derive(Debug, Serialize, Default, ToSchema)]
+pub struct AccuracyStats {
+    pub mean_error_minutes: Option<u32>,
+    pub median_error_minutes: Option<u32>,
+    pub close_guesses: u32,
+    pub best_guess: Option<BestGuess>,
+}
+
+impl AccuracyStats {
+    pub fn compute(user: &User, pictures: &[Picture]) -> Result<AccuracyStats> {
+        let mut errors: Vec<(Day, u32)> = vec![];
+        for (&day, guess) in user.guess_data.iter() {
+            if let Some(picture) = pictures.iter().find(|p| p.day() == day) {
+                errors.push((day, utils::time_diff_minutes(picture, guess.hm)?));
+            }
+        }
+        Ok(Self::from_errors(errors))
+    }
+
+    fn from_errors(mut errors: Vec<(Day, u32)>) -> AccuracyStats {
+        if errors.is_empty() {
+            return AccuracyStats::default();
+        }
+
+        // earliest day first so that the best guess is stable between equal errors
+        errors.sort();
+        let best_guess = errors
+            .iter()
+            .min_by_key(|(_, error)| *error)
+            .map(|&(day, error_minutes)| BestGuess { day, error_minutes });
+
+        let mut minutes: Vec<u32> = errors.iter().map(|(_, error)| *error).collect();
+        minutes.sort_unstable();
+
+        let total: u32 = minutes.iter().sum();
+        let mean = (total as f64 / minutes.len() as f64).round() as u32;
+
+        AccuracyStats {
+            mean_error_minutes: Some(mean),
+            median_error_minutes: Some(median(&minutes)),
+            close_guesses: minutes
+                .iter()
+                .filter(|&&error| error <= CLOSE_GUESS_MINUTES)
+                .count() as u32,
+            best_guess,
+        }
+    }
+}
+
+/// Median of sorted values, averaging the two middle ones when there is an even count
+fn median(sorted: &[u32]) -> u32 {
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]).div_ceil(2)
+    } else {
+        sorted[middle]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median() {
+        assert_eq!(5, median(&[1, 5, 90]));
+        assert_eq!(8, median(&[1, 5, 10, 90]));
+    }
+
+    #[test]
+    fn test_from_errors() {
+        let stats = AccuracyStats::from_errors(vec![(3, 40), (1, 2), (2, 15), (4, 2)]);
+        assert_eq!(Some(15), stats.mean_error_minutes);
+        assert_eq!(Some(9), stats.median_error_minutes);
+        assert_eq!(3, stats.close_guesses);
+
+        let best_guess = stats.best_guess.unwrap();
+        assert_eq!(1, best_guess.day);
+        assert_eq!(2, best_guess.error_minutes);
+    }
+
+    #[test]
+    fn test_from_errors_without_guesses() {
+        let stats = AccuracyStats::from_errors(vec![]);
+        assert_eq!(None, stats.mean_error_minutes);
+        assert!(stats.best_guess.is_none());
+    }
+}
@@ -0,0 +1,222 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Utc};
+use log::info;
+use rust_i18n::t;
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use crate::{
+    database::{picture_meta_repository::PictureMetaRepository, user_repository::UserRepository},
+    models::{
+        achievement::{Achievement, UnlockedAchievement},
+        picture::Picture,
+        user::{GuessData, User},
+    },
+    utils::{self, Day},
+};
+
+const STREAK_LENGTH: u32 = 5;
+const EARLY_BIRD_MINUTES: i64 = 10;
+const CALENDAR_DAYS: usize = 25;
+
+fn has_perfect_minute(guess_data: &HashMap<Day, GuessData>, pictures: &[Picture]) -> Result<bool> {
+    for (day, guess) in guess_data {
+        if let Some(picture) = pictures.iter().find(|p| p.day() == *day)
+            && utils::time_diff_minutes(picture, guess.hm)? == 0
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn has_streak(guess_data: &HashMap<Day, GuessData>) -> bool {
+    guess_data.keys().any(|&day| {
+        (day..day + STREAK_LENGTH).all(|streak_day| guess_data.contains_key(&streak_day))
+    })
+}
+
+fn is_early_bird(guess_data: &HashMap<Day, GuessData>) -> bool {
+    guess_data.iter().any(|(&day, guess)| {
+        match utils::get_picture_release_time(guess.taken_at.year(), day) {
+            Some(release_time) => {
+                let delay = guess.taken_at - release_time;
+                delay >= Duration::zero() && delay <= Duration::minutes(EARLY_BIRD_MINUTES)
+            }
+            None => false,
+        }
+    })
+}
+
+fn has_full_calendar(guess_data: &HashMap<Day, GuessData>) -> bool {
+    guess_data.len() >= CALENDAR_DAYS
+}
+
+/// Returns every achievement whose rule holds for the given guesses
+pub fn evaluate(
+    guess_data: &HashMap<Day, GuessData>,
+    pictures: &[Picture],
+) -> Result<Vec<Achievement>> {
+    let mut achievements = vec![];
+    for achievement in Achievement::ALL {
+        let unlocked = match achievement {
+            Achievement::PerfectMinute => has_perfect_minute(guess_data, pictures)?,
+            Achievement::Streak5 => has_streak(guess_data),
+            Achievement::EarlyBird => is_early_bird(guess_data),
+            Achievement::FullCalendar => has_full_calendar(guess_data),
+        };
+
+        if unlocked {
+            achievements.push(achievement);
+        }
+    }
+    Ok(achievements)
+}
+
+/// Evaluates the rules against the user guesses and stores the newly unlocked achievements
+pub fn unlock_new_achievements(user: &mut User) -> Result<Vec<Achievement>> {
+    let pictures = PictureMetaRepository::get_all_pictures()?;
+    unlock_achievements(user, &pictures, Utc::now())
+}
+
+fn unlock_achievements(
+    user: &mut User,
+    pictures: &[Picture],
+    now: DateTime<Utc>,
+) -> Result<Vec<Achievement>> {
+    let new_achievements: Vec<_> = evaluate(&user.guess_data, pictures)?
+        .into_iter()
+        .filter(|achievement| !user.has_achievement(*achievement))
+        .collect();
+
+    for achievement in new_achievements.iter() {
+        info!(
+            "user {} unlocked achievement: {achievement:?}",
+            user.username
+        );
+        user.achievements.push(UnlockedAchievement {
+            achievement: *achievement,
+            unlocked_at: now,
+        });
+    }
+
+    Ok(new_achievements)
+}
+
+fn backfill(users: &mut [User], pictures: &[Picture], now: DateTime<Utc>) -> Result<usize> {
+    let mut unlocked = 0;
+    for user in users.iter_mut() {
+        unlocked += unlock_achievements(user, pictures, now)?.len();
+    }
+    Ok(unlocked)
+}
+
+/// Unlocks the achievements earned by guesses made before their rules existed,
+/// otherwise players would only get them on their next guess
+pub fn backfill_achievements() -> Result<()> {
+    let pictures = PictureMetaRepository::get_all_pictures()?;
+    let mut users = UserRepository::get_all_users()?;
+
+    let unlocked = backfill(&mut users, &pictures, Utc::now())?;
+    if unlocked > 0 {
+        UserRepository::update_all_users(users)?;
+        info!("backfilled {unlocked} achievements");
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BadgeDto {
+    pub icon: String,
+    pub name: String,
+    pub description: String,
+    pub unlocked_at: String,
+}
+
+pub fn to_badges(achievements: &[UnlockedAchievement], locale: &str) -> Vec<BadgeDto> {
+    achievements
+        .iter()
+        .map(|unlocked| {
+            let key = unlocked.achievement.key();
+            BadgeDto {
+                icon: unlocked.achievement.icon().to_string(),
+                name: t!(format!("achievements.{key}"), locale = locale).to_string(),
+                description: t!(format!("achievements.{key}_description"), locale = locale)
+                    .to_string(),
+                unlocked_at: unlocked.unlocked_at.format("%Y-%m-%d %H:%M").to_string(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn guess(hm: (u32, u32), day: Day, hour: u32, minute: u32) -> GuessData {
+        let taken_at = Utc
+            .with_ymd_and_hms(2025, 12, day, hour, minute, 0)
+            .unwrap();
+        GuessData::new(hm, taken_at)
+    }
+
+    #[test]
+    fn test_perfect_minute() {
+        let pictures = vec![Picture::for_test(1, "12:34"), Picture::for_test(2, "08:00")];
+        let guesses = HashMap::from([(1, guess((12, 35), 1, 18, 0))]);
+        assert!(!has_perfect_minute(&guesses, &pictures).unwrap());
+
+        let guesses = HashMap::from([(2, guess((8, 0), 2, 18, 0))]);
+        assert!(has_perfect_minute(&guesses, &pictures).unwrap());
+    }
+
+    #[test]
+    fn test_streak_requires_consecutive_days() {
+        let guesses: HashMap<_, _> = [1, 2, 3, 5, 6]
+            .into_iter()
+            .map(|d| (d, guess((12, 0), d, 18, 0)))
+            .collect();
+        assert!(!has_streak(&guesses));
+
+        let guesses: HashMap<_, _> = (3..=7).map(|d| (d, guess((12, 0), d, 18, 0))).collect();
+        assert!(has_streak(&guesses));
+    }
+
+    #[test]
+    fn test_early_bird() {
+        // pictures are released at 5:00 UTC
+        let guesses = HashMap::from([(4, guess((12, 0), 4, 5, 11))]);
+        assert!(!is_early_bird(&guesses));
+
+        let guesses = HashMap::from([(4, guess((12, 0), 4, 5, 10))]);
+        assert!(is_early_bird(&guesses));
+    }
+
+    #[test]
+    fn test_full_calendar() {
+        let guesses: HashMap<_, _> = (1..=24).map(|d| (d, guess((12, 0), d, 18, 0))).collect();
+        assert!(!has_full_calendar(&guesses));
+
+        let guesses: HashMap<_, _> = (1..=25).map(|d| (d, guess((12, 0), d, 18, 0))).collect();
+        assert!(has_full_calendar(&guesses));
+    }
+    #[test]
+    fn test_backfill_unlocks_missing_achievements_once() {
+        let mut player = User::for_test("player");
+        player.guess_data = (1..=25).map(|d| (d, guess((12, 0), d, 18, 0))).collect();
+        let mut users = vec![player];
+        let pictures: Vec<_> = (1..=25).map(|d| Picture::for_test(d, "08:00")).collect();
+        let now = Utc.with_ymd_and_hms(2025, 12, 26, 12, 0, 0).unwrap();
+
+        assert_eq!(2, backfill(&mut users, &pictures, now).unwrap());
+        assert!(users[0].has_achievement(Achievement::Streak5));
+        assert!(users[0].has_achievement(Achievement::FullCalendar));
+        assert!(!users[0].has_achievement(Achievement::PerfectMinute));
+
+        assert_eq!(0, backfill(&mut users, &pictures, now).unwrap());
+        assert_eq!(2, users[0].achievements.len());
+    }
+}
@@ -1,14 +1,21 @@
 use anyhow::Result;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 pub const CONFIG_RAW: &str = include_str!("../config.toml");
 
 #[derive(Deserialize)]
 pub struct Config {
     pub hostname: String,
+    /// URL players reach the webapp at, through the reverse proxy
+    pub public_url: String,
     pub dev_mode: bool,
     pub oauth2: OAuth2Providers,
     pub score: ScoreConfig,
+    pub joker: JokerConfig,
+    pub teams: TeamsConfig,
+    pub leaderboard: LeaderboardConfig,
+    pub rate_limit: RateLimitConfig,
+    pub security_headers: SecurityHeadersConfig,
 }
 
 impl Config {
@@ -18,11 +25,71 @@ impl Config {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct ScoreConfig {
     pub max_reward: f64,
-    pub exponent: f64,
-    pub divider: u32,
+    #[serde(flatten)]
+    pub curve: ScoringCurve,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "curve", rename_all = "lowercase")]
+pub enum ScoringCurve {
+    Linear { divider: u32 },
+    Power { exponent: f64, divider: u32 },
+    Gaussian { sigma: f64 },
+    Stepped { steps: Vec<ScoreStep> },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ScoreStep {
+    /// Maximum difference in minutes for this step to apply
+    pub within: u32,
+    /// Fraction of `max_reward` awarded by this step
+    pub ratio: f64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct JokerConfig {
+    pub enabled: bool,
+    /// Factor applied to the points of the day the joker was played on
+    pub multiplier: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TeamsConfig {
+    pub enabled: bool,
+    /// Whether players can pick their own team from their profile
+    pub self_select: bool,
+    /// IDs of the users allowed to create teams and assign players to them
+    pub admins: Vec<String>,
+    #[serde(flatten)]
+    pub aggregation: TeamAggregation,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(tag = "aggregation", rename_all = "snake_case")]
+pub enum TeamAggregation {
+    Sum,
+    Average,
+    BestN { n: usize },
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LeaderboardConfig {
+    /// Applied in order to players with the same score, until one of them separates them
+    pub tiebreakers: Vec<Tiebreaker>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Tiebreaker {
+    /// More guesses on the exact minute
+    PerfectGuesses,
+    /// Lower sum of the differences in minutes
+    TotalError,
+    /// Reached their score first
+    EarliestSubmission,
 }
 
 #[derive(Deserialize, Debug)]
@@ -43,3 +110,34 @@ pub struct OAuth2Config {
     pub scope: String,
     pub secret: String,
 }
+
+#[derive(Deserialize, Debug)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Reverse proxies in front of the webapp, each one appends an entry to `X-Forwarded-For`
+    pub trusted_proxy_hops: usize,
+    /// Guess submissions, from the website or the API
+    pub guess: BucketConfig,
+    /// OAuth2 login redirects
+    pub login: BucketConfig,
+    /// Pictures of the days
+    pub images: BucketConfig,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct BucketConfig {
+    /// Requests allowed at once
+    pub burst: u32,
+    /// Requests allowed per minute once the burst is spent
+    pub per_minute: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SecurityHeadersConfig {
+    pub content_security_policy: String,
+    pub referrer_policy: String,
+    pub frame_options: String,
+    /// Whether the webapp is served over HTTPS by the reverse proxy, enables HSTS
+    pub behind_tls: bool,
+    pub hsts_max_age: u32,
+}
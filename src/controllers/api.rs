@@ -0,0 +1,426 @@
+use anyhow::{Context, Result};
+use chrono::{Duration, Utc};
+use log::debug;
+use rtfw_http::{
+    http::{HttpRequest, HttpResponse, response_status_codes::HttpStatusCode},
+    router::RoutingData,
+};
+use serde::Serialize;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::{
+    accuracy::AccuracyStats,
+    achievements::{self, BadgeDto},
+    controllers::{
+        day::{self, DayDto, DayStatsDto},
+        guess::{self, SubmitGuessRequest},
+        leaderboard::{self, LeaderboardUserEntry},
+        profile::{self, UserGuessDay},
+    },
+    database::{
+        api_token_repository::ApiTokenRepository,
+        leaderboard_history_repository::LeaderboardHistoryRepository,
+        picture_meta_repository::PictureMetaRepository, user_repository::UserRepository,
+    },
+    http_helpers,
+    models::{api_token::TokenScope, user::User},
+    openapi::ApiDoc,
+    ranking,
+    rate_limit::{self, RouteGroup},
+    routes::{self, CalendarEntry},
+    security,
+    utils::{self, Day},
+};
+
+const LAST_USED_RESOLUTION: Duration = Duration::minutes(1);
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiError {
+    /// Stable identifier of the error, meant to be matched on by clients
+    #[schema(example = "already_guessed")]
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiErrorResponse {
+    pub error: ApiError,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarResponse {
+    pub game_ended: bool,
+    pub days: Vec<CalendarEntry>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GuessResponse {
+    pub points: u32,
+    /// Keys of the achievements unlocked by this guess
+    pub new_achievements: Vec<String>,
+    /// The day, now including the guess and the real time
+    pub day: DayDto,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MeResponse {
+    pub username: String,
+    pub total_score: u32,
+    /// Rank among the players shown on the leaderboard, `None` for hidden players
+    pub rank: Option<u32>,
+    #[schema(value_type = Option<u32>)]
+    pub joker_day: Option<Day>,
+    pub team_id: Option<String>,
+    pub days: Vec<UserGuessDay>,
+    pub badges: Vec<BadgeDto>,
+    pub accuracy: AccuracyStats,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LeaderboardResponse {
+    pub users: Vec<LeaderboardUserEntry>,
+}
+
+/// Every API error has the same body: `{"error": {"code": "...", "message": "..."}}`
+pub fn api_error(status: HttpStatusCode, code: &str, message: &str) -> Result<HttpResponse> {
+    let body = ApiErrorResponse {
+        error: ApiError {
+            code: code.to_string(),
+            message: message.to_string(),
+        },
+    };
+    http_helpers::response()
+        .set_status(status)
+        .set_json_body(&body)?
+        .build()
+}
+
+fn not_found(message: &str) -> Result<HttpResponse> {
+    api_error(HttpStatusCode::NotFound, "not_found", message)
+}
+
+/// Unknown routes under `/api/` answer with the same error body as the known ones
+pub fn catcher_api_404(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+    let path = request.url.split('?').next().unwrap_or_default();
+    not_found(&format!("No API route matches {path}"))
+}
+
+fn unauthorized() -> Result<HttpResponse> {
+    api_error(
+        HttpStatusCode::Unauthorized,
+        "unauthorized",
+        "You need to be logged in to access this resource",
+    )
+}
+
+fn invalid_token() -> Result<HttpResponse> {
+    api_error(
+        HttpStatusCode::Unauthorized,
+        "invalid_token",
+        "This API token is invalid or has been revoked",
+    )
+}
+
+fn too_many_requests(retry_after: std::time::Duration) -> Result<HttpResponse> {
+    let body = ApiErrorResponse {
+        error: ApiError {
+            code: "rate_limited".to_string(),
+            message: "Too many requests, retry later".to_string(),
+        },
+    };
+    http_helpers::response()
+        .set_status(HttpStatusCode::TooManyRequests)
+        .set_header("Retry-After", &http_helpers::retry_after_secs(retry_after))
+        .set_json_body(&body)?
+        .build()
+}
+
+fn ok<T: Serialize>(value: &T) -> Result<HttpResponse> {
+    http_helpers::response().set_json_body(value)?.build()
+}
+
+fn get_day_from_route(routing_data: &RoutingData) -> Option<Day> {
+    match routing_data.get_value::<Day>("id") {
+        Ok(Some(day)) if utils::is_day_valid(day) => Some(day),
+        other => {
+            debug!("invalid day requested through the api: {other:?}");
+            None
+        }
+    }
+}
+
+/// Resolves the player behind an API request, either from an API token or from the session
+/// cookie, which grants every scope. The error is the response to send back as is.
+fn get_api_user(
+    request: &HttpRequest,
+    required_scope: TokenScope,
+) -> Result<Result<Option<User>, HttpResponse>> {
+    let token = match http_helpers::get_authorization_bearer(request) {
+        Some(token) => token,
+        None => return Ok(Ok(http_helpers::get_logged_in_user(request)?)),
+    };
+
+    let token_hash = security::hash_api_token(&token);
+    let mut api_token = match ApiTokenRepository::get_token_by_hash(&token_hash)? {
+        Some(api_token) => api_token,
+        None => {
+            debug!("unknown api token used");
+            return Ok(Err(invalid_token()?));
+        }
+    };
+
+    if !api_token.scope.allows(required_scope) {
+        return Ok(Err(api_error(
+            HttpStatusCode::Forbidden,
+            "insufficient_scope",
+            "This token is not allowed to perform this action",
+        )?));
+    }
+
+    let user = match UserRepository::get_user_by_id(&api_token.user_id)? {
+        Some(user) => user,
+        None => return Ok(Err(invalid_token()?)),
+    };
+
+    // avoid rewriting the tokens file on every single request of a chatty bot
+    let now = Utc::now();
+    if api_token
+        .last_used_at
+        .is_none_or(|last_used_at| now - last_used_at > LAST_USED_RESOLUTION)
+    {
+        api_token.last_used_at = Some(now);
+        ApiTokenRepository::update_token(api_token)?;
+    }
+
+    Ok(Ok(Some(user)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/openapi.json",
+    tag = "meta",
+    responses((status = 200, description = "This OpenAPI document", content_type = "application/json"))
+)]
+pub fn get_openapi(_request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+    ok(&ApiDoc::openapi())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/calendar",
+    tag = "days",
+    security((), ("api_token" = []), ("session" = [])),
+    responses(
+        (status = 200, description = "Days of the calendar", body = CalendarResponse),
+        (status = 401, description = "Invalid API token", body = ApiErrorResponse),
+    )
+)]
+pub fn get_calendar(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+    let user = match get_api_user(request, TokenScope::ReadOnly)? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+    ok(&CalendarResponse {
+        game_ended: utils::is_game_over(),
+        days: routes::get_calendar_entries(user.as_ref()),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/days/{id}",
+    tag = "days",
+    params(("id" = u32, Path, description = "Day of the calendar, from 1 to 25")),
+    security((), ("api_token" = []), ("session" = [])),
+    responses(
+        (status = 200, description = "Metadata of the day, with your guess once submitted", body = DayDto),
+        (status = 401, description = "Invalid API token", body = ApiErrorResponse),
+        (status = 404, description = "The day does not exist or is not released yet", body = ApiErrorResponse),
+    )
+)]
+pub fn get_day(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    let day = match get_day_from_route(routing_data) {
+        Some(day) => day,
+        None => return not_found("This day does not exist or is not released yet"),
+    };
+
+    let user = match get_api_user(request, TokenScope::ReadOnly)? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+    ok(&day::build_day_dto(day, user.as_ref())?)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/days/{id}/stats",
+    tag = "days",
+    params(("id" = u32, Path, description = "Day of the calendar, from 1 to 25")),
+    security((), ("api_token" = []), ("session" = [])),
+    responses(
+        (status = 200, description = "Results of the day", body = DayStatsDto),
+        (status = 401, description = "Invalid API token", body = ApiErrorResponse),
+        (status = 403, description = "The day was not guessed yet and the game is not over", body = ApiErrorResponse),
+        (status = 404, description = "The day does not exist or is not released yet", body = ApiErrorResponse),
+    )
+)]
+pub fn get_day_stats(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    let day = match get_day_from_route(routing_data) {
+        Some(day) => day,
+        None => return not_found("This day does not exist or is not released yet"),
+    };
+
+    let user = match get_api_user(request, TokenScope::ReadOnly)? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+    if !day::are_day_results_available(day, user.as_ref()) {
+        return api_error(
+            HttpStatusCode::Forbidden,
+            "results_unavailable",
+            "Results are available once you guessed this day or the game is over",
+        );
+    }
+
+    let picture = PictureMetaRepository::get_picture(day)?.context("picture should exist")?;
+    let users = UserRepository::get_all_users()?;
+    ok(&day::compute_day_stats(&picture, &users)?)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/days/{id}/guess",
+    tag = "days",
+    params(("id" = u32, Path, description = "Day of the calendar, from 1 to 25")),
+    request_body = SubmitGuessRequest,
+    security(("api_token" = ["submit_guess"]), ("session" = [])),
+    responses(
+        (status = 200, description = "The guess was accepted", body = GuessResponse),
+        (status = 400, description = "The guess was refused", body = ApiErrorResponse),
+        (status = 401, description = "Not logged in or invalid API token", body = ApiErrorResponse),
+        (status = 403, description = "The game is over or the token cannot submit guesses", body = ApiErrorResponse),
+        (status = 404, description = "The day does not exist or is not released yet", body = ApiErrorResponse),
+        (status = 429, description = "Too many guesses, see the `Retry-After` header", body = ApiErrorResponse),
+    )
+)]
+pub fn post_day_guess(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    if let Some(retry_after) = rate_limit::check_ip(request, RouteGroup::Guess)? {
+        return too_many_requests(retry_after);
+    }
+
+    let user = match get_api_user(request, TokenScope::SubmitGuess)? {
+        Ok(Some(user)) => user,
+        Ok(None) => return unauthorized(),
+        Err(response) => return Ok(response),
+    };
+
+    if let Some(retry_after) = rate_limit::check_user(&user.id, RouteGroup::Guess)? {
+        return too_many_requests(retry_after);
+    }
+
+    let day = match get_day_from_route(routing_data) {
+        Some(day) => day,
+        None => return not_found("This day does not exist or is not released yet"),
+    };
+
+    let request_data = match http_helpers::get_json_body::<SubmitGuessRequest>(request) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!("failed to create guess struct from json: {e}");
+            return api_error(
+                HttpStatusCode::BadRequest,
+                "invalid_body",
+                "Expected a JSON body like {\"guess\": \"HH:MM\", \"joker\": false}",
+            );
+        }
+    };
+
+    let user_id = user.id.to_owned();
+    match guess::submit_guess(user, day, &request_data.guess, request_data.joker)? {
+        Ok(outcome) => {
+            let achievements = outcome
+                .new_achievements
+                .iter()
+                .map(|a| a.key().to_string())
+                .collect();
+            // the day now includes the guess and the real time
+            let user = UserRepository::get_user_by_id(&user_id)?;
+            ok(&GuessResponse {
+                points: outcome.points,
+                new_achievements: achievements,
+                day: day::build_day_dto(day, user.as_ref())?,
+            })
+        }
+        Err(rejection) => {
+            let locale = http_helpers::get_user_locale(request)?.as_str();
+            api_error(
+                rejection.status(),
+                rejection.code(),
+                &rejection.message(locale),
+            )
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me",
+    tag = "players",
+    security(("api_token" = []), ("session" = [])),
+    responses(
+        (status = 200, description = "Your profile, scores and accuracy", body = MeResponse),
+        (status = 401, description = "Not logged in or invalid API token", body = ApiErrorResponse),
+    )
+)]
+pub fn get_me(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+    let user = match get_api_user(request, TokenScope::ReadOnly)? {
+        Ok(Some(user)) => user,
+        Ok(None) => return unauthorized(),
+        Err(response) => return Ok(response),
+    };
+
+    let locale = http_helpers::get_user_locale(request)?.as_str();
+    let pictures = PictureMetaRepository::get_all_pictures()?;
+    let rank = ranking::get_standings()?
+        .visible
+        .iter()
+        .find(|p| p.user.id == user.id)
+        .map(|p| p.rank);
+
+    ok(&MeResponse {
+        total_score: user.get_total_score()?,
+        rank,
+        joker_day: user.get_joker_day(),
+        days: profile::get_user_guess_days(&user),
+        badges: achievements::to_badges(&user.achievements, locale),
+        accuracy: AccuracyStats::compute(&user, &pictures)?,
+        username: user.username,
+        team_id: user.team_id,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/leaderboard",
+    tag = "players",
+    security((), ("api_token" = []), ("session" = [])),
+    responses(
+        (status = 200, description = "The global leaderboard", body = LeaderboardResponse),
+        (status = 401, description = "Invalid API token", body = ApiErrorResponse),
+    )
+)]
+pub fn get_leaderboard(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+    if let Err(response) = get_api_user(request, TokenScope::ReadOnly)? {
+        return Ok(response);
+    }
+
+    let locale = http_helpers::get_user_locale(request)?.as_str();
+    let standings = ranking::get_standings()?;
+    let previous_snapshot = LeaderboardHistoryRepository::get_latest_snapshot()?;
+    ok(&LeaderboardResponse {
+        users: leaderboard::get_leaderboard_users(&standings, locale, previous_snapshot.as_ref())?,
+    })
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{debug, info};
+use rtfw_http::{
+    http::{HttpRequest, HttpResponse},
+    router::RoutingData,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    database::api_token_repository::ApiTokenRepository,
+    http_helpers::{self, bad_request, bad_request_msg, unauthorized},
+    models::api_token::{ApiToken, TokenScope},
+    security, utils,
+};
+
+const TOKEN_ID_LENGTH: usize = 12;
+const MAX_TOKEN_NAME_LENGTH: usize = 32;
+const MAX_TOKENS_PER_USER: usize = 10;
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateTokenRequest {
+    pub name: String,
+    pub scope: TokenScope,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiTokenEntry {
+    pub id: String,
+    pub name: String,
+    pub scope: TokenScope,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+pub fn get_token_entries(user_id: &str) -> Result<Vec<ApiTokenEntry>> {
+    let mut tokens = ApiTokenRepository::get_tokens_of_user(user_id)?;
+    tokens.sort_by_key(|t| t.created_at);
+    Ok(tokens
+        .into_iter()
+        .map(|token| ApiTokenEntry {
+            id: token.id,
+            name: token.name,
+            scope: token.scope,
+            created_at: token.created_at,
+            last_used_at: token.last_used_at,
+        })
+        .collect())
+}
+
+/// Tokens can only be managed from the website, never with another token
+pub fn post_create_token(
+    request: &HttpRequest,
+    _routing_data: &RoutingData,
+) -> Result<HttpResponse> {
+    let user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return unauthorized(),
+    };
+
+    let request_data = match http_helpers::get_json_body::<CreateTokenRequest>(request) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!("failed to create api token struct from json: {e}");
+            return bad_request();
+        }
+    };
+
+    let name = request_data.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_TOKEN_NAME_LENGTH {
+        return bad_request_msg("Invalid token name!");
+    }
+
+    if ApiTokenRepository::get_tokens_of_user(&user.id)?.len() >= MAX_TOKENS_PER_USER {
+        return bad_request_msg("You have too many API tokens, revoke some first!");
+    }
+
+    let token = security::generate_api_token();
+    let api_token = ApiToken {
+        id: utils::generate_code(TOKEN_ID_LENGTH).to_lowercase(),
+        user_id: user.id.to_owned(),
+        name: name.to_string(),
+        token_hash: security::hash_api_token(&token),
+        scope: request_data.scope,
+        created_at: Utc::now(),
+        last_used_at: None,
+    };
+    let token_id = api_token.id.to_owned();
+
+    info!(
+        "user {} created api token: {}",
+        user.username, api_token.name
+    );
+    ApiTokenRepository::create_token(api_token)?;
+
+    // this is the only time the token is ever shown
+    http_helpers::response()
+        .set_json_body(&json!({"id": token_id, "token": token}))?
+        .build()
+}
+
+pub fn post_revoke_token(
+    request: &HttpRequest,
+    routing_data: &RoutingData,
+) -> Result<HttpResponse> {
+    let user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return unauthorized(),
+    };
+
+    let token_id: Option<String> = match routing_data.get_value("id") {
+        Ok(token_id) => token_id,
+        Err(e) => {
+            debug!("invalid api token ID format: {e}");
+            return bad_request();
+        }
+    };
+
+    let api_token = ApiTokenRepository::get_tokens_of_user(&user.id)?
+        .into_iter()
+        .find(|t| Some(&t.id) == token_id.as_ref());
+    let api_token = match api_token {
+        Some(api_token) => api_token,
+        None => return bad_request(),
+    };
+
+    info!(
+        "user {} revoked api token: {}",
+        user.username, api_token.name
+    );
+    ApiTokenRepository::delete_token(&api_token)?;
+
+    http_helpers::response().build()
+}
@@ -0,0 +1,168 @@
+use anyhow::Result;
+use log::debug;
+use rtfw_http::{
+    http::{HttpRequest, HttpResponse, response_status_codes::HttpStatusCode},
+    router::RoutingData,
+};
+use std::sync::LazyLock;
+
+use crate::{embedded, http_helpers};
+
+/// The content behind a hashed URL never changes, so browsers may keep it forever
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+#[derive(Debug)]
+struct PublicAsset {
+    path: &'static str,
+    url: String,
+    /// Same as `url` with the content hash before the extension, like `/main.3f2a9c1b.css`
+    hashed_url: String,
+}
+
+static PUBLIC_ASSETS: LazyLock<Vec<PublicAsset>> = LazyLock::new(|| {
+    embedded::list("src")
+        .filter_map(|path| {
+            let url = get_public_url(path)?;
+            let hash = embedded::content_hash(path)?;
+            Some(PublicAsset {
+                path,
+                hashed_url: get_hashed_url(&url, hash),
+                url,
+            })
+        })
+        .collect()
+});
+
+/// Only styles, scripts, images, fonts and the favicon are public, views and markdown are not
+fn get_public_url(path: &str) -> Option<String> {
+    if path == "src/assets/favicon.ico" {
+        return Some("/favicon.ico".to_string());
+    }
+
+    if let Some(name) = path.strip_prefix("src/styles/") {
+        Some(format!("/{name}"))
+    } else if let Some(name) = path.strip_prefix("src/scripts/") {
+        Some(format!("/scripts/{name}"))
+    } else if let Some(name) = path.strip_prefix("src/assets/images/") {
+        Some(format!("/static/images/{name}"))
+    } else {
+        path.strip_prefix("src/assets/fonts/")
+            .map(|name| format!("/static/fonts/{name}"))
+    }
+}
+
+fn get_hashed_url(url: &str, hash: &str) -> String {
+    let (dir, file_name) = url.rsplit_once('/').unwrap_or(("", url));
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{dir}/{stem}.{hash}.{extension}"),
+        None => format!("{dir}/{file_name}.{hash}"),
+    }
+}
+
+/// Every URL an asset can be requested with, each one gets its own route
+pub fn get_asset_urls() -> Vec<&'static str> {
+    PUBLIC_ASSETS
+        .iter()
+        .flat_map(|asset| [asset.url.as_str(), asset.hashed_url.as_str()])
+        .collect()
+}
+
+/// URL to use in the views, it changes whenever the content of the asset does
+pub fn versioned_url(url: &str) -> String {
+    if embedded::is_reading_from_disk() {
+        return url.to_string();
+    }
+
+    PUBLIC_ASSETS
+        .iter()
+        .find(|asset| asset.url == url)
+        .map_or_else(|| url.to_string(), |asset| asset.hashed_url.to_owned())
+}
+
+fn not_found() -> Result<HttpResponse> {
+    http_helpers::response()
+        .set_status(HttpStatusCode::NotFound)
+        .build()
+}
+
+pub fn get_asset(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+    let url = request.url.split('?').next().unwrap_or_default();
+    let Some(asset) = PUBLIC_ASSETS
+        .iter()
+        .find(|asset| asset.url == url || asset.hashed_url == url)
+    else {
+        debug!("no asset found for {url}");
+        return not_found();
+    };
+
+    let content = match embedded::read(asset.path) {
+        Ok(content) => content,
+        Err(e) => {
+            debug!("failed to read asset {}: {e:#}", asset.path);
+            return not_found();
+        }
+    };
+
+    let mime_type = mime_guess::from_path(asset.path).first_or_octet_stream();
+    let mut response = http_helpers::response()
+        .set_raw_body(content.into_owned())
+        .set_content_type(mime_type.as_ref());
+
+    if asset.hashed_url == url && !embedded::is_reading_from_disk() {
+        response = response.set_header("Cache-Control", IMMUTABLE_CACHE_CONTROL);
+    }
+
+    response.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_public_url() {
+        assert_eq!(
+            Some("/main.css"),
+            get_public_url("src/styles/main.css").as_deref()
+        );
+        assert_eq!(
+            Some("/scripts/day.js"),
+            get_public_url("src/scripts/day.js").as_deref()
+        );
+        assert_eq!(
+            Some("/static/fonts/0xProto/0xProtoNerdFont-Regular.ttf"),
+            get_public_url("src/assets/fonts/0xProto/0xProtoNerdFont-Regular.ttf").as_deref()
+        );
+        assert_eq!(
+            Some("/favicon.ico"),
+            get_public_url("src/assets/favicon.ico").as_deref()
+        );
+        assert_eq!(None, get_public_url("src/assets/about.md"));
+        assert_eq!(None, get_public_url("src/views/index.hbs"));
+    }
+
+    #[test]
+    fn test_get_hashed_url() {
+        assert_eq!(
+            "/main.3f2a9c1b.css",
+            get_hashed_url("/main.css", "3f2a9c1b")
+        );
+        assert_eq!(
+            "/scripts/day.3f2a9c1b.js",
+            get_hashed_url("/scripts/day.js", "3f2a9c1b")
+        );
+        assert_eq!(
+            "/static/LICENSE.3f2a9c1b",
+            get_hashed_url("/static/LICENSE", "3f2a9c1b")
+        );
+    }
+
+    #[test]
+    fn test_versioned_url() {
+        let url = versioned_url("/main.css");
+        let hash = embedded::content_hash("src/styles/main.css").unwrap();
+        assert_eq!(format!("/main.{hash}.css"), url);
+        assert!(get_asset_urls().contains(&url.as_str()));
+        assert_eq!("/unknown.css", versioned_url("/unknown.css"));
+    }
+}
@@ -1,11 +1,10 @@
 use anyhow::{Context, Result};
 use log::{debug, warn};
 use rtfw_http::{
-    http::{HttpRequest, HttpResponse, HttpResponseBuilder, response_status_codes::HttpStatusCode},
+    http::{HttpRequest, HttpResponse, response_status_codes::HttpStatusCode},
     router::RoutingData,
 };
-use rust_i18n::t;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::json;
 
 use crate::{
@@ -17,8 +16,10 @@ use crate::{
         microsoft_user_response::MicrosoftUserInfoHandler,
         oauth_user_info_handler::OAuthUserInfoHandler,
     },
-    oauth2, routes, security,
-    utils::{self, capitalize},
+    oauth2,
+    rate_limit::{self, RouteGroup},
+    routes, security, templates,
+    utils::capitalize,
 };
 
 pub fn get_login(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
@@ -26,15 +27,13 @@ pub fn get_login(request: &HttpRequest, _routing_data: &RoutingData) -> Result<H
         return redirect("/auth/me");
     }
 
-    let data = json!({
-        "i18n": I18n::from_request(request).unwrap(),
-    });
+    let data = json!({});
 
-    let rendered = utils::render_view("login", &data)?;
-    HttpResponseBuilder::new().set_html_body(&rendered).build()
+    let rendered = templates::render(request, "login", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
 }
 
-pub fn get_logout(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+pub fn post_logout(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
     let mut user = match http_helpers::get_logged_in_user(request)? {
         Some(user) => user,
         None => return redirect("/auth/login"),
@@ -44,14 +43,18 @@ pub fn get_logout(request: &HttpRequest, _routing_data: &RoutingData) -> Result<
     UserRepository::update_user(user)?;
 
     let clear_bearer_cookie = http_helpers::create_clear_bearer_cookie();
-    HttpResponseBuilder::new()
+    http_helpers::response()
         .set_status(HttpStatusCode::Found)
-        .set_cookie(clear_bearer_cookie)
+        .set_header("Set-Cookie", &clear_bearer_cookie)
         .set_header("Location", "/")
         .build()
 }
 
 pub fn get_oauth2_login(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    if let Some(retry_after) = rate_limit::check_ip(request, RouteGroup::Login)? {
+        return http_helpers::too_many_requests(retry_after);
+    }
+
     let provider = request.query.get("idp").context("IDP should be provided")?;
     let oauth2_config = security::get_oauth2_provider_config(provider)?;
 
@@ -79,8 +82,8 @@ fn handle_access_token_response_error(request: &HttpRequest) -> Result<HttpRespo
         "error_description": error_description.replace("+", " "),
     });
 
-    let rendered = utils::render_view("oauth2_error", &data)?;
-    HttpResponseBuilder::new().set_html_body(&rendered).build()
+    let rendered = templates::render(request, "oauth2_error", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
 }
 
 fn oauth2_redirect<T: for<'a> Deserialize<'a>>(
@@ -88,6 +91,10 @@ fn oauth2_redirect<T: for<'a> Deserialize<'a>>(
     config: &OAuth2Config,
     response_creator: impl OAuthUserInfoHandler<T>,
 ) -> Result<HttpResponse> {
+    if let Some(retry_after) = rate_limit::check_ip(request, RouteGroup::Login)? {
+        return http_helpers::too_many_requests(retry_after);
+    }
+
     if request.query.contains_key("error") {
         return handle_access_token_response_error(request);
     }
@@ -116,9 +123,9 @@ fn oauth2_redirect<T: for<'a> Deserialize<'a>>(
     }
 
     let bearer_cookie = http_helpers::create_bearer_cookie(&oauth2_response);
-    HttpResponseBuilder::new()
+    http_helpers::response()
         .set_status(HttpStatusCode::Found)
-        .set_cookie(bearer_cookie)
+        .set_header("Set-Cookie", &bearer_cookie)
         .set_header("Location", "/")
         .build()
 }
@@ -146,19 +153,3 @@ pub fn get_discord_oauth2_redirect(
     let config = security::get_oauth2_provider_config("discord")?;
     oauth2_redirect(request, &config, DiscordUserInfoHandler {})
 }
-
-#[derive(Serialize)]
-struct I18n {
-    title: String,
-    login_with: String,
-}
-
-impl I18n {
-    fn from_request(request: &HttpRequest) -> Result<I18n> {
-        let user_locale = http_helpers::get_user_locale(request)?.to_str();
-        Ok(I18n {
-            title: t!("auth.title", locale = user_locale).to_string(),
-            login_with: t!("auth.login_with", locale = user_locale).to_string(),
-        })
-    }
-}
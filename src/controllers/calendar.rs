@@ -0,0 +1,124 @@
+use anyhow::Result;
+use chrono::{Datelike, TimeDelta, Utc};
+use rtfw_http::{
+    http::{HttpRequest, HttpResponse},
+    router::RoutingData,
+};
+use rust_i18n::t;
+
+use crate::{
+    config::Config,
+    http_helpers,
+    icalendar::{self, Event},
+    utils::{self, Day},
+};
+
+/// Unlocks only last for a moment, a short event is shown better than an empty one
+const UNLOCK_EVENT_MINUTES: i64 = 15;
+
+fn get_unlock_event(
+    year: i32,
+    day: Day,
+    public_url: &str,
+    host: &str,
+    locale: &str,
+) -> Option<Event> {
+    let start = utils::get_picture_release_time(year, day)?;
+    Some(Event {
+        uid: format!("unlock-{year}-{day}@{host}"),
+        start,
+        end: start + TimeDelta::minutes(UNLOCK_EVENT_MINUTES),
+        summary: t!("calendar.unlock_summary", locale = locale, day = day).into_owned(),
+        description: t!("calendar.unlock_description", locale = locale, day = day).into_owned(),
+        url: format!("{public_url}/day/{day}"),
+        alarm: true,
+    })
+}
+
+fn get_game_end_event(year: i32, public_url: &str, host: &str, locale: &str) -> Option<Event> {
+    let start = utils::get_game_end_time(year)?;
+    Some(Event {
+        uid: format!("game-end-{year}@{host}"),
+        start,
+        end: start + TimeDelta::minutes(1),
+        summary: t!("calendar.game_end_summary", locale = locale).into_owned(),
+        description: t!("calendar.game_end_description", locale = locale).into_owned(),
+        url: format!("{public_url}/leaderboard"),
+        alarm: true,
+    })
+}
+
+/// One event per unlock of the edition of the given year, followed by the end of the game
+fn get_edition_events(year: i32, public_url: &str, locale: &str) -> Vec<Event> {
+    let host = url::Url::parse(public_url)
+        .ok()
+        .and_then(|url| url.host_str().map(String::from))
+        .unwrap_or_else(|| "advent-of-time".to_string());
+
+    (1..=25)
+        .filter_map(|day| get_unlock_event(year, day, public_url, &host, locale))
+        .chain(get_game_end_event(year, public_url, &host, locale))
+        .collect()
+}
+
+/// iCalendar file of the unlock times, so that players can get reminders from their calendar app
+pub fn get_calendar_ics(
+    request: &HttpRequest,
+    _routing_data: &RoutingData,
+) -> Result<HttpResponse> {
+    let public_url = Config::get()?.public_url;
+    let public_url = public_url.trim_end_matches('/');
+    let locale = http_helpers::get_user_locale(request)?.as_str();
+    let now = Utc::now();
+
+    let events = get_edition_events(now.year(), public_url, locale);
+    let ics = icalendar::to_ics(&t!("title", locale = locale), &events, now);
+    http_helpers::response()
+        .set_raw_body(ics.into_bytes())
+        .set_content_type("text/calendar; charset=utf-8")
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_get_edition_events() {
+        let events = get_edition_events(2025, "https://aot.example", "en");
+        assert_eq!(26, events.len());
+
+        let first = &events[0];
+        assert_eq!("unlock-2025-1@aot.example", first.uid);
+        assert_eq!(
+            Utc.with_ymd_and_hms(2025, 12, 1, 5, 0, 0).unwrap(),
+            first.start
+        );
+        assert_eq!("https://aot.example/day/1", first.url);
+        assert_eq!("Day 1 picture unlocks", first.summary);
+
+        let game_end = events.last().unwrap();
+        assert_eq!("game-end-2025@aot.example", game_end.uid);
+        assert_eq!(
+            Utc.with_ymd_and_hms(2025, 12, 25, 22, 59, 0).unwrap(),
+            game_end.start
+        );
+    }
+
+    #[test]
+    fn test_get_edition_events_are_localized() {
+        let events = get_edition_events(2025, "https://aot.example", "fr");
+        assert_eq!("La photo du jour 3 est disponible", events[2].summary);
+    }
+
+    #[test]
+    fn test_uids_are_unique() {
+        let events = get_edition_events(2025, "not a url", "en");
+        let mut uids: Vec<_> = events.iter().map(|event| event.uid.as_str()).collect();
+        uids.sort();
+        uids.dedup();
+        assert_eq!(events.len(), uids.len());
+        assert!(uids.iter().all(|uid| uid.ends_with("@advent-of-time")));
+    }
+}
@@ -1,17 +1,21 @@
 use anyhow::{Context, Result};
 use log::debug;
 use rtfw_http::{
-    http::{HttpRequest, HttpResponse, HttpResponseBuilder, response_status_codes::HttpStatusCode},
+    http::{HttpRequest, HttpResponse, response_status_codes::HttpStatusCode},
     router::RoutingData,
 };
-use rust_i18n::t;
 use serde::Serialize;
 use serde_json::json;
 use std::fs;
+use utoipa::ToSchema;
 
 use crate::{
-    database::picture_meta_repository::PictureMetaRepository,
-    http_helpers, routes,
+    config::Config,
+    database::{picture_meta_repository::PictureMetaRepository, user_repository::UserRepository},
+    http_helpers,
+    models::{picture::Picture, user::User},
+    rate_limit::{self, RouteGroup},
+    routes, templates,
     utils::{self, Day},
 };
 
@@ -35,14 +39,46 @@ pub fn get_single_day(request: &HttpRequest, routing_data: &RoutingData) -> Resu
     }
 
     let body = load_day_view(request, day)?;
-    HttpResponseBuilder::new().set_html_body(&body).build()
+    http_helpers::response().set_html_body(&body).build()
 }
 
-pub fn get_day_picture(_request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+pub fn get_day_results(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    let day: Result<Option<u32>> = routing_data.get_value("id");
+    let day = match day {
+        Ok(Some(day)) if utils::is_day_valid(day) => day,
+        _ => {
+            debug!("invalid day requested for results: {day:?}");
+            return routes::catcher_get_404(request, routing_data);
+        }
+    };
+
+    let user = http_helpers::get_logged_in_user(request)?;
+    if !are_day_results_available(day, user.as_ref()) {
+        debug!("results for day {day} are not available yet");
+        return routes::catcher_get_404(request, routing_data);
+    }
+
+    let picture = PictureMetaRepository::get_picture(day)?.context("picture should exist")?;
+    let users = UserRepository::get_all_users()?;
+    let stats = compute_day_stats(&picture, &users)?;
+
+    let data = json!({
+        "stats": stats,
+        "username": user.map(|u| u.username),
+    });
+    let rendered = templates::render(request, "day_results", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
+}
+
+pub fn get_day_picture(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    if let Some(retry_after) = rate_limit::check_ip(request, RouteGroup::Images)? {
+        return http_helpers::too_many_requests(retry_after);
+    }
+
     let day: Result<Option<u32>> = routing_data.get_value("id");
     if day.is_err() {
         debug!("invalid day ID format for img: {day:?}");
-        return HttpResponseBuilder::new()
+        return http_helpers::response()
             .set_status(HttpStatusCode::NotFound)
             .build();
     }
@@ -50,7 +86,7 @@ pub fn get_day_picture(_request: &HttpRequest, routing_data: &RoutingData) -> Re
     let day = day.unwrap();
     if day.is_none() {
         debug!("no day ID provided for img");
-        return HttpResponseBuilder::new()
+        return http_helpers::response()
             .set_status(HttpStatusCode::NotFound)
             .build();
     }
@@ -58,7 +94,7 @@ pub fn get_day_picture(_request: &HttpRequest, routing_data: &RoutingData) -> Re
     let day = day.unwrap();
     if !utils::is_day_valid(day) {
         debug!("invalid day requested for img: {day}");
-        return HttpResponseBuilder::new()
+        return http_helpers::response()
             .set_status(HttpStatusCode::NotFound)
             .build();
     }
@@ -71,21 +107,22 @@ pub fn get_day_picture(_request: &HttpRequest, routing_data: &RoutingData) -> Re
 
     if let Ok(bin_content) = fs::read(&picture_path) {
         debug!("valid day img returned: {:?}", picture_path);
-        HttpResponseBuilder::new()
+        http_helpers::response()
             .set_raw_body(bin_content)
             .set_content_type(mime_type.as_ref())
             .build()
     } else {
         debug!("invalid day img requested: {:?}", picture_path);
-        HttpResponseBuilder::new()
+        http_helpers::response()
             .set_status(HttpStatusCode::NotFound)
             .build()
     }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DayDto {
+    #[schema(value_type = u32)]
     pub id: Day,
     pub img_src: String,
     pub img_alt: String,
@@ -95,26 +132,124 @@ pub struct DayDto {
     pub real_time: Option<String>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct GuessDataDto {
     pub time: String,
     pub points: u32,
+    pub joker: bool,
 }
 
-fn load_day_view(request: &HttpRequest, day: u32) -> Result<String> {
-    let day_img_src = format!("/day-pic/{day}");
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DayResultEntry {
+    pub rank: String,
+    pub username: String,
+    pub time: String,
+    pub points: u32,
+    pub error_minutes: u32,
+    pub submitted_at: String,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    pub label: String,
+    pub count: usize,
+    /// Height of the bar, relative to the biggest bucket (0-100)
+    pub height: usize,
+    pub contains_real_time: bool,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DayStatsDto {
+    #[schema(value_type = u32)]
+    pub id: Day,
+    pub real_time: String,
+    pub guesses: usize,
+    pub average_error_minutes: Option<u32>,
+    pub results: Vec<DayResultEntry>,
+    pub histogram: Vec<HistogramBucket>,
+}
+
+pub fn compute_day_stats(picture: &Picture, users: &[User]) -> Result<DayStatsDto> {
+    let day = picture.day();
+    let mut guesses = users
+        .iter()
+        .filter(|u| !u.hidden)
+        .filter_map(|u| u.guess_data.get(&day).map(|guess| (u, guess)))
+        .map(|(user, guess)| -> Result<_> {
+            let error_minutes = utils::time_diff_minutes(picture, guess.hm)?;
+            Ok((user, guess, error_minutes, user.get_points(day)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Best guesses first, earliest submission wins when points are equal
+    guesses.sort_by(|a, b| b.3.cmp(&a.3).then(a.1.taken_at.cmp(&b.1.taken_at)));
+
+    let results = guesses
+        .iter()
+        .enumerate()
+        .map(
+            |(rank, (user, guess, error_minutes, points))| DayResultEntry {
+                rank: (rank + 1).to_string(),
+                username: user.username.to_owned(),
+                time: guess.time(),
+                points: *points,
+                error_minutes: *error_minutes,
+                submitted_at: guess.taken_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            },
+        )
+        .collect::<Vec<_>>();
+
+    let mut counts = [0usize; 24];
+    for (_, guess, _, _) in guesses.iter() {
+        counts[guess.hm.0 as usize % 24] += 1;
+    }
+
+    let max_count = counts.iter().copied().max().unwrap_or(0).max(1);
+    let real_hour = picture.hours()?;
+    let histogram = counts
+        .iter()
+        .enumerate()
+        .map(|(hour, &count)| HistogramBucket {
+            label: format!("{hour:02}h"),
+            count,
+            height: count * 100 / max_count,
+            contains_real_time: hour as u32 == real_hour,
+        })
+        .collect();
+
+    let average_error_minutes = if guesses.is_empty() {
+        None
+    } else {
+        let total_error: u32 = guesses.iter().map(|g| g.2).sum();
+        Some(total_error / guesses.len() as u32)
+    };
+
+    Ok(DayStatsDto {
+        id: day,
+        real_time: picture.time_taken.to_owned(),
+        guesses: results.len(),
+        average_error_minutes,
+        results,
+        histogram,
+    })
+}
+
+/// Metadata of a day as seen by the given user, the real time is only revealed once they guessed
+pub fn build_day_dto(day: Day, user: Option<&User>) -> Result<DayDto> {
     let picture_meta =
         PictureMetaRepository::get_picture(day)?.context("picture should exist bruh")?;
 
-    let user = http_helpers::get_logged_in_user(request)?;
-    let authenticated = user.is_some();
     let guess_data = match user {
         Some(user) if user.has_guessed(day) => {
             let guess_data = user.guess_data.get(&day).unwrap();
             Some(GuessDataDto {
                 time: guess_data.time(),
                 points: user.get_points(day)?,
+                joker: guess_data.joker,
             })
         }
         _ => None,
@@ -126,55 +261,121 @@ fn load_day_view(request: &HttpRequest, day: u32) -> Result<String> {
         None
     };
 
+    Ok(DayDto {
+        id: day,
+        img_src: format!("/day-pic/{day}"),
+        img_alt: format!("Image for day {day}"),
+        date_hint: picture_meta.original_date,
+        location_hint: picture_meta.location,
+        guess_data,
+        real_time: solution_time,
+    })
+}
+
+/// Whether the results of a day can be shown: once the game is over or the user guessed it
+pub fn are_day_results_available(day: Day, user: Option<&User>) -> bool {
+    utils::is_game_over() || user.is_some_and(|u| u.has_guessed(day))
+}
+
+fn load_day_view(request: &HttpRequest, day: u32) -> Result<String> {
+    let user = http_helpers::get_logged_in_user(request)?;
+    let authenticated = user.is_some();
+    let joker_config = Config::get()?.joker;
+    let joker_available =
+        joker_config.enabled && user.as_ref().is_some_and(|u| !u.has_used_joker());
+
     let data = json!({
         "title": &format!("Day {day}"),
         "authenticated": authenticated,
-        "day": DayDto {
-            id: day,
-            img_src: day_img_src,
-            img_alt: format!("Image for day {day}"),
-            date_hint: picture_meta.original_date,
-            location_hint: picture_meta.location,
-            guess_data,
-            real_time: solution_time,
-        },
-        "i18n": I18n::from_request(request).unwrap(),
+        "jokerAvailable": joker_available,
+        "jokerMultiplier": joker_config.multiplier,
+        "day": build_day_dto(day, user.as_ref())?,
     });
 
-    let rendered = utils::render_view("day", &data)?;
+    let rendered = templates::render(request, "day", &data)?;
     Ok(rendered)
 }
 
-#[derive(Serialize)]
-struct I18n {
-    already_guessed: String,
-    guess_today: String,
-    hint_original_date: String,
-    hint_location: String,
-    hint_real_time: String,
-    hint_your_guess: String,
-    hint_your_points: String,
-    check_progress: String,
-    check_point_system: String,
-    submit_text: String,
-    login_required: String,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user::GuessData;
+    use chrono::{TimeZone, Utc};
 
-impl I18n {
-    fn from_request(request: &HttpRequest) -> Result<I18n> {
-        let user_locale = http_helpers::get_user_locale(request)?.to_str();
-        Ok(I18n {
-            already_guessed: t!("day.already_guessed", locale = user_locale).to_string(),
-            guess_today: t!("day.guess_today", locale = user_locale).to_string(),
-            hint_original_date: t!("day.hint_original_date", locale = user_locale).to_string(),
-            hint_location: t!("day.hint_location", locale = user_locale).to_string(),
-            hint_real_time: t!("day.hint_real_time", locale = user_locale).to_string(),
-            hint_your_guess: t!("day.hint_your_guess", locale = user_locale).to_string(),
-            hint_your_points: t!("day.hint_your_points", locale = user_locale).to_string(),
-            check_progress: t!("day.check_progress", locale = user_locale).to_string(),
-            check_point_system: t!("check_point_system", locale = user_locale).to_string(),
-            submit_text: t!("day.submit_text", locale = user_locale).to_string(),
-            login_required: t!("day.login_required", locale = user_locale).to_string(),
-        })
+    fn player(username: &str, hm: (u32, u32), points: u32, submitted_at: (u32, u32)) -> User {
+        let taken_at = Utc
+            .with_ymd_and_hms(2025, 12, 3, submitted_at.0, submitted_at.1, 0)
+            .unwrap();
+        let mut guess = GuessData::new(hm, taken_at);
+        guess.points = Some(points);
+
+        let mut user = User::for_test(username);
+        user.guess_data.insert(3, guess);
+        user
+    }
+
+    #[test]
+    fn test_compute_day_stats_ranks_by_points_then_submission() {
+        let mut hidden = player("hidden", (12, 30), 200, (6, 0));
+        hidden.hidden = true;
+        let users = vec![
+            player("late", (12, 30), 200, (10, 0)),
+            player("far", (23, 30), 50, (8, 0)),
+            player("early", (12, 30), 200, (9, 0)),
+            hidden,
+            User::for_test("absent"),
+        ];
+
+        let stats = compute_day_stats(&Picture::for_test(3, "12:30"), &users).unwrap();
+        let usernames: Vec<_> = stats.results.iter().map(|r| r.username.as_str()).collect();
+        assert_eq!(vec!["early", "late", "far"], usernames);
+        assert_eq!("1", stats.results[0].rank);
+        assert_eq!(660, stats.results[2].error_minutes);
+        assert_eq!(3, stats.guesses);
+        assert_eq!(Some(220), stats.average_error_minutes);
+    }
+
+    #[test]
+    fn test_compute_day_stats_histogram() {
+        let users = vec![
+            player("a", (12, 10), 190, (9, 0)),
+            player("b", (12, 50), 190, (9, 0)),
+            // stored guesses are not validated again, an hour past 23 wraps around
+            player("c", (24, 5), 20, (9, 0)),
+        ];
+
+        let stats = compute_day_stats(&Picture::for_test(3, "12:30"), &users).unwrap();
+        assert_eq!(24, stats.histogram.len());
+
+        let noon = &stats.histogram[12];
+        assert_eq!("12h", noon.label);
+        assert_eq!((2, 100), (noon.count, noon.height));
+        assert!(noon.contains_real_time);
+
+        let midnight = &stats.histogram[0];
+        assert_eq!((1, 50), (midnight.count, midnight.height));
+        assert!(!midnight.contains_real_time);
+        assert_eq!(
+            1,
+            stats
+                .histogram
+                .iter()
+                .filter(|b| b.contains_real_time)
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_compute_day_stats_without_guesses() {
+        let stats = compute_day_stats(&Picture::for_test(3, "12:30"), &[]).unwrap();
+        assert_eq!(0, stats.guesses);
+        assert_eq!(None, stats.average_error_minutes);
+        assert!(
+            stats
+                .histogram
+                .iter()
+                .all(|b| b.count == 0 && b.height == 0)
+        );
+        assert!(stats.histogram[12].contains_real_time);
     }
 }
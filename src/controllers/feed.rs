@@ -0,0 +1,143 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, SecondsFormat, Utc};
+use rtfw_http::{
+    http::{HttpRequest, HttpResponse},
+    router::RoutingData,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    config::Config, database::picture_meta_repository::PictureMetaRepository, http_helpers,
+    models::picture::Picture, templates, utils,
+};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FeedEntry {
+    day: u32,
+    url: String,
+    picture_url: String,
+    picture_type: String,
+    /// RFC 3339 time at which the picture was released
+    published: String,
+    /// Only revealed once the game is over
+    time_taken: Option<String>,
+}
+
+/// Released days, the latest first, as feed readers expect
+fn get_feed_entries(
+    pictures: &[Picture],
+    now: DateTime<Utc>,
+    game_over: bool,
+    public_url: &str,
+) -> Vec<FeedEntry> {
+    let mut entries: Vec<_> = pictures
+        .iter()
+        .filter(|picture| utils::is_picture_released(now, picture.day()))
+        .map(|picture| {
+            let day = picture.day();
+            let published = utils::get_picture_release_time(now.year(), day).unwrap_or(now);
+            FeedEntry {
+                day,
+                url: format!("{public_url}/day/{day}"),
+                picture_url: format!("{public_url}/day-pic/{day}"),
+                picture_type: mime_guess::from_path(&picture.path)
+                    .first_or_octet_stream()
+                    .to_string(),
+                published: published.to_rfc3339_opts(SecondsFormat::Secs, true),
+                time_taken: game_over.then(|| picture.time_taken.to_owned()),
+            }
+        })
+        .collect();
+
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.day));
+    entries
+}
+
+/// Atom feed of the released days, so that players can follow the calendar from their reader
+pub fn get_feed(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+    let public_url = Config::get()?.public_url;
+    let public_url = public_url.trim_end_matches('/');
+    let now = Utc::now();
+    let pictures = PictureMetaRepository::get_all_pictures()?;
+    let entries = get_feed_entries(&pictures, now, utils::is_game_over(), public_url);
+
+    // the feed changes whenever a day is released
+    let updated = entries
+        .first()
+        .map(|entry| entry.published.to_owned())
+        .unwrap_or_else(|| now.to_rfc3339_opts(SecondsFormat::Secs, true));
+    let data = json!({
+        "siteUrl": public_url,
+        "updated": updated,
+        "entries": entries,
+    });
+
+    let rendered = templates::render(request, "feed", &data)?;
+    http_helpers::response()
+        .set_raw_body(rendered.into_bytes())
+        .set_content_type("application/atom+xml; charset=utf-8")
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::Locale;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_get_feed_entries_lists_released_days() {
+        let pictures: Vec<_> = (1..=25)
+            .map(|day| Picture::for_test(day, "07:05"))
+            .collect();
+        // 4:59 UTC is 5:59 CET, the picture of the day is not released yet
+        let now = Utc.with_ymd_and_hms(2025, 12, 3, 4, 59, 0).unwrap();
+        let entries = get_feed_entries(&pictures, now, false, "https://aot.example");
+
+        let days: Vec<_> = entries.iter().map(|entry| entry.day).collect();
+        assert_eq!(vec![2, 1], days);
+        assert_eq!("https://aot.example/day/2", entries[0].url);
+        assert_eq!("https://aot.example/day-pic/2", entries[0].picture_url);
+        assert_eq!("image/jpeg", entries[0].picture_type);
+        assert_eq!("2025-12-02T05:00:00Z", entries[0].published);
+        assert_eq!(None, entries[0].time_taken);
+    }
+
+    #[test]
+    fn test_get_feed_entries_reveal_time_once_game_is_over() {
+        let now = Utc.with_ymd_and_hms(2025, 12, 26, 12, 0, 0).unwrap();
+        let entries = get_feed_entries(
+            &[Picture::for_test(25, "07:05")],
+            now,
+            true,
+            "https://aot.example",
+        );
+        assert_eq!(Some("07:05"), entries[0].time_taken.as_deref());
+    }
+
+    #[test]
+    fn test_feed_view_renders_entries() {
+        let now = Utc.with_ymd_and_hms(2025, 12, 26, 12, 0, 0).unwrap();
+        let entries = get_feed_entries(
+            &[Picture::for_test(1, "07:05")],
+            now,
+            true,
+            "https://aot.example",
+        );
+        let data = json!({
+            "siteUrl": "https://aot.example",
+            "updated": entries[0].published,
+            "entries": entries,
+        });
+
+        let rendered = templates::render_localized("feed", Locale::default(), &data).unwrap();
+        assert!(rendered.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>"));
+        assert!(rendered.contains("<title>Day 1</title>"));
+        assert!(rendered.contains("<link href=\"https://aot.example/day/1\"/>"));
+        assert!(rendered.contains("<published>2025-12-01T05:00:00Z</published>"));
+        assert!(rendered.contains("07:05"));
+        assert!(!rendered.contains("<html"));
+    }
+}
@@ -1,131 +1,336 @@
-use anyhow::{Context, Result, bail, ensure};
+use anyhow::{Context, Result, ensure};
 use chrono::Utc;
-use log::{debug, info, trace};
+use log::{debug, info};
 use rtfw_http::{
-    http::{HttpRequest, HttpResponse, HttpResponseBuilder, response_status_codes::HttpStatusCode},
+    http::{HttpRequest, HttpResponse, response_status_codes::HttpStatusCode},
     router::RoutingData,
 };
+use rust_i18n::t;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use utoipa::ToSchema;
 
 use crate::{
+    achievements,
+    config::Config,
+    controllers::api,
     database::{picture_meta_repository::PictureMetaRepository, user_repository::UserRepository},
-    http_helpers::{self, bad_request, bad_request_msg},
-    models::user::GuessData,
-    utils,
+    http_helpers::{self, bad_request, unauthorized},
+    models::{
+        achievement::Achievement,
+        user::{GuessData, User},
+    },
+    rate_limit::{self, RouteGroup},
+    utils::{self, Day},
 };
 
-#[derive(Serialize, Deserialize)]
+/// Body of a guess, the day always comes from the path of the endpoint
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct SubmitGuessRequest {
-    pub day: u32,
+    /// Time of the guess, e.g. `19:05`, `7:05`, `07h05` or `7:05 PM`
+    #[schema(example = "19:05")]
     pub guess: String,
+    #[serde(default)]
+    pub joker: bool,
 }
 
-pub fn post_guess(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
-    if utils::is_game_over() {
-        return bad_request_msg("The game has ended!");
+/// Why a guess value could not be understood as a time of the day
+#[derive(Debug, Clone, PartialEq)]
+pub enum GuessParseError {
+    UnknownFormat,
+    HourOutOfRange(u32),
+    MinutesOutOfRange(u32),
+}
+
+impl GuessParseError {
+    pub fn message(&self, locale: &str) -> String {
+        match self {
+            GuessParseError::UnknownFormat => t!("guess.invalid_format", locale = locale),
+            GuessParseError::HourOutOfRange(hour) => {
+                t!("guess.invalid_hour", locale = locale, hour = hour)
+            }
+            GuessParseError::MinutesOutOfRange(minutes) => {
+                t!("guess.invalid_minutes", locale = locale, minutes = minutes)
+            }
+        }
+        .to_string()
     }
+}
 
-    let mut user = match http_helpers::get_logged_in_user(request)? {
-        Some(user) => user,
-        None => {
-            return HttpResponseBuilder::new()
-                .set_status(HttpStatusCode::Unauthorized)
-                .build();
+/// Reasons for which a guess can be refused, shown to the player
+#[derive(Debug, Clone, PartialEq)]
+pub enum GuessRejection {
+    GameOver,
+    InvalidDay,
+    AlreadyGuessed,
+    JokersDisabled,
+    JokerAlreadyPlayed,
+    InvalidGuess(GuessParseError),
+}
+
+impl GuessRejection {
+    /// Stable identifier used by API clients
+    pub fn code(&self) -> &'static str {
+        match self {
+            GuessRejection::GameOver => "game_over",
+            GuessRejection::InvalidDay => "invalid_day",
+            GuessRejection::AlreadyGuessed => "already_guessed",
+            GuessRejection::JokersDisabled => "jokers_disabled",
+            GuessRejection::JokerAlreadyPlayed => "joker_already_played",
+            GuessRejection::InvalidGuess(_) => "invalid_guess",
         }
-    };
+    }
 
-    let body = match request.get_str_body() {
-        Ok(body) => body,
-        Err(e) => {
-            trace!("invalid guess format: {:?}", request.body);
-            debug!("failed to get str body: {e}");
-            return bad_request();
+    pub fn status(&self) -> HttpStatusCode {
+        match self {
+            GuessRejection::InvalidDay => HttpStatusCode::NotFound,
+            GuessRejection::GameOver => HttpStatusCode::Forbidden,
+            _ => HttpStatusCode::BadRequest,
         }
-    };
+    }
 
-    let request_data = match serde_json::from_str::<SubmitGuessRequest>(&body) {
-        Ok(value) => value,
-        Err(e) => {
-            trace!("invalid guess json format: {:?}", &body);
-            debug!("failed to create guess struct from json: {e}");
-            return bad_request();
+    pub fn message(&self, locale: &str) -> String {
+        match self {
+            GuessRejection::GameOver => t!("guess.game_over", locale = locale).to_string(),
+            GuessRejection::InvalidDay => t!("guess.invalid_day", locale = locale).to_string(),
+            GuessRejection::AlreadyGuessed => {
+                t!("guess.already_guessed", locale = locale).to_string()
+            }
+            GuessRejection::JokersDisabled => {
+                t!("guess.jokers_disabled", locale = locale).to_string()
+            }
+            GuessRejection::JokerAlreadyPlayed => {
+                t!("guess.joker_already_played", locale = locale).to_string()
+            }
+            GuessRejection::InvalidGuess(error) => error.message(locale),
         }
-    };
+    }
+}
+
+#[derive(Debug)]
+pub struct GuessOutcome {
+    pub points: u32,
+    pub new_achievements: Vec<Achievement>,
+}
+
+/// Validates and records a guess for the given user, shared by the website and the API
+pub fn submit_guess(
+    mut user: User,
+    day: Day,
+    guess_value: &str,
+    joker: bool,
+) -> Result<Result<GuessOutcome, GuessRejection>> {
+    if utils::is_game_over() {
+        return Ok(Err(GuessRejection::GameOver));
+    }
 
-    let day = request_data.day;
     if !utils::is_day_valid(day) {
         debug!("invalid day requested: {day}");
-        return bad_request();
+        return Ok(Err(GuessRejection::InvalidDay));
     }
 
     if user.has_guessed(day) {
-        return bad_request_msg("You have already guessed this day!");
+        return Ok(Err(GuessRejection::AlreadyGuessed));
+    }
+
+    if joker {
+        if !Config::get()?.joker.enabled {
+            return Ok(Err(GuessRejection::JokersDisabled));
+        }
+
+        if let Some(joker_day) = user.get_joker_day() {
+            debug!(
+                "user {} already played joker on day {joker_day}",
+                user.username
+            );
+            return Ok(Err(GuessRejection::JokerAlreadyPlayed));
+        }
     }
 
-    let guess_value = request_data.guess;
-    match parse_guess_value(&guess_value) {
-        Ok(guess) => {
-            info!("received guess for day {day}: {guess:?}");
-            let picture = PictureMetaRepository::get_picture(day)?
-                .context("picture should exist this guessed day")?;
+    let guess = match parse_guess_value(guess_value) {
+        Ok(guess) => guess,
+        Err(e) => {
+            debug!("invalid guess value {guess_value:?}: {e:?}");
+            return Ok(Err(GuessRejection::InvalidGuess(e)));
+        }
+    };
 
-            ensure!(picture.day() == day);
+    info!("received guess for day {day}: {guess:?}");
+    let picture = PictureMetaRepository::get_picture(day)?
+        .context("picture should exist this guessed day")?;
 
-            debug!("guessed time: {:02}:{:02}", guess.0, guess.1);
-            debug!("real time: {}", picture.time_taken);
+    ensure!(picture.day() == day);
 
-            let real_time_mins = picture.hours()? * 60 + picture.minutes()?;
-            let guess_time_mins = guess.0 * 60 + guess.1;
+    debug!("guessed time: {:02}:{:02}", guess.0, guess.1);
+    debug!("real time: {}", picture.time_taken);
 
-            debug!("guessed time: {:02}:{:02}", guess.0, guess.1);
-            debug!("real time: {}", picture.time_taken);
+    let diff_mins = utils::time_diff_minutes(&picture, guess)?;
+    debug!("diff in minutes: {diff_mins}");
 
-            let diff_mins = (real_time_mins).abs_diff(guess_time_mins);
-            debug!("diff in minutes: {diff_mins}");
+    let mut guess_data = GuessData::new(guess, Utc::now());
+    guess_data.joker = joker;
+    guess_data.points = Some(utils::compute_score(&picture, guess)?);
+    user.guess_data.insert(day, guess_data);
 
-            let points = utils::compute_score(&picture, guess)?;
-            debug!("user {} scored {points} points", user.username);
+    let points = user.get_points(day)?;
+    debug!("user {} scored {points} points", user.username);
 
-            let guess_data = GuessData::new(guess, Utc::now());
-            user.guess_data.insert(day, guess_data);
-            UserRepository::update_user(user)?;
+    let new_achievements = achievements::unlock_new_achievements(&mut user)?;
+    UserRepository::update_user(user)?;
 
-            HttpResponseBuilder::new()
-                .set_json_body(&json!({"points": points}))?
-                .build()
+    Ok(Ok(GuessOutcome {
+        points,
+        new_achievements,
+    }))
+}
+
+pub fn post_guess(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    if let Some(retry_after) = rate_limit::check_ip(request, RouteGroup::Guess)? {
+        return http_helpers::too_many_requests(retry_after);
+    }
+
+    let user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return unauthorized(),
+    };
+
+    if let Some(retry_after) = rate_limit::check_user(&user.id, RouteGroup::Guess)? {
+        return http_helpers::too_many_requests(retry_after);
+    }
+
+    let day: Day = match routing_data.get_value("id") {
+        Ok(Some(day)) => day,
+        other => {
+            debug!("invalid guess day: {other:?}");
+            return bad_request();
+        }
+    };
+
+    let request_data = match http_helpers::get_json_body::<SubmitGuessRequest>(request) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!("failed to create guess struct from json: {e}");
+            return bad_request();
         }
-        Err(err) => {
-            let error_json = json!({
-                "error": err.to_string()
-            });
-
-            HttpResponseBuilder::new()
-                .set_status(HttpStatusCode::BadRequest)
-                .set_json_body(&error_json)?
+    };
+
+    match submit_guess(user, day, &request_data.guess, request_data.joker)? {
+        Ok(outcome) => {
+            let badges: Vec<_> = outcome.new_achievements.iter().map(|a| a.icon()).collect();
+            http_helpers::response()
+                .set_json_body(&json!({"points": outcome.points, "badges": badges}))?
                 .build()
         }
+        Err(rejection) => {
+            let locale = http_helpers::get_user_locale(request)?.as_str();
+            api::api_error(
+                rejection.status(),
+                rejection.code(),
+                &rejection.message(locale),
+            )
+        }
     }
 }
 
-fn parse_guess_value(guess: &str) -> Result<(u32, u32)> {
-    if guess.len() > 5 {
-        bail!("guess value does not denote a valid time");
-    }
-
-    let parts = guess
-        .split_once(':')
-        .context("guess value should contain 'h'")?;
-    let hour: u32 = parts.0.parse()?;
-    let minutes: u32 = parts.1.parse()?;
+/// Parses a time of the day, written either as `H:MM` or `HhMM`, on 24 hours or followed by
+/// `AM`/`PM`
+pub fn parse_guess_value(guess: &str) -> Result<(u32, u32), GuessParseError> {
+    let guess = guess.trim().to_lowercase();
+    let (time, meridiem) = if let Some(time) = guess.strip_suffix("am") {
+        (time.trim_end(), Some(Meridiem::Am))
+    } else if let Some(time) = guess.strip_suffix("pm") {
+        (time.trim_end(), Some(Meridiem::Pm))
+    } else {
+        (guess.as_str(), None)
+    };
 
-    if hour > 23 {
-        bail!("invalid hour format: {hour}");
+    let (hour, minutes) = time
+        .split_once([':', 'h'])
+        .ok_or(GuessParseError::UnknownFormat)?;
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    if !is_number(hour) || hour.len() > 2 || !is_number(minutes) || minutes.len() != 2 {
+        return Err(GuessParseError::UnknownFormat);
     }
 
+    let hour: u32 = hour.parse().map_err(|_| GuessParseError::UnknownFormat)?;
+    let minutes: u32 = minutes
+        .parse()
+        .map_err(|_| GuessParseError::UnknownFormat)?;
     if minutes > 59 {
-        bail!("invalid minute format: {hour}");
+        return Err(GuessParseError::MinutesOutOfRange(minutes));
     }
 
+    let hour = match meridiem {
+        None if hour > 23 => return Err(GuessParseError::HourOutOfRange(hour)),
+        None => hour,
+        Some(_) if hour == 0 || hour > 12 => return Err(GuessParseError::HourOutOfRange(hour)),
+        Some(Meridiem::Am) => hour % 12,
+        Some(Meridiem::Pm) => hour % 12 + 12,
+    };
+
     Ok((hour, minutes))
 }
+
+enum Meridiem {
+    Am,
+    Pm,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_guess_value_24_hours() {
+        assert_eq!(parse_guess_value("19:05"), Ok((19, 5)));
+        assert_eq!(parse_guess_value("07:05"), Ok((7, 5)));
+        assert_eq!(parse_guess_value("7:05"), Ok((7, 5)));
+        assert_eq!(parse_guess_value("00:00"), Ok((0, 0)));
+        assert_eq!(parse_guess_value(" 23:59 "), Ok((23, 59)));
+    }
+
+    #[test]
+    fn test_parse_guess_value_h_separator() {
+        assert_eq!(parse_guess_value("07h05"), Ok((7, 5)));
+        assert_eq!(parse_guess_value("7h05"), Ok((7, 5)));
+        assert_eq!(parse_guess_value("19H30"), Ok((19, 30)));
+    }
+
+    #[test]
+    fn test_parse_guess_value_meridiem() {
+        assert_eq!(parse_guess_value("7:05 PM"), Ok((19, 5)));
+        assert_eq!(parse_guess_value("7:05pm"), Ok((19, 5)));
+        assert_eq!(parse_guess_value("7:05 am"), Ok((7, 5)));
+        assert_eq!(parse_guess_value("12:30 AM"), Ok((0, 30)));
+        assert_eq!(parse_guess_value("12:30 PM"), Ok((12, 30)));
+        assert_eq!(
+            parse_guess_value("13:00 PM"),
+            Err(GuessParseError::HourOutOfRange(13))
+        );
+        assert_eq!(
+            parse_guess_value("0:15 AM"),
+            Err(GuessParseError::HourOutOfRange(0))
+        );
+    }
+
+    #[test]
+    fn test_parse_guess_value_invalid() {
+        for guess in [
+            "", "7", "705", "7:5", "7:055", "123:00", "-1:00", "7:05 XM", "a:bc", "7:05:00",
+        ] {
+            assert_eq!(
+                parse_guess_value(guess),
+                Err(GuessParseError::UnknownFormat),
+                "{guess:?} should be invalid"
+            );
+        }
+        assert_eq!(
+            parse_guess_value("24:00"),
+            Err(GuessParseError::HourOutOfRange(24))
+        );
+        assert_eq!(
+            parse_guess_value("12:60"),
+            Err(GuessParseError::MinutesOutOfRange(60))
+        );
+    }
+}
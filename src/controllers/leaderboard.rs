@@ -1,91 +1,172 @@
 use anyhow::Result;
+use log::debug;
 use rtfw_http::{
-    http::{HttpRequest, HttpResponse, HttpResponseBuilder},
+    http::{HttpRequest, HttpResponse},
     router::RoutingData,
 };
-use rust_i18n::t;
 use serde::Serialize;
 use serde_json::json;
-use std::cmp;
+use std::{collections::HashMap, sync::Arc};
+use utoipa::ToSchema;
 
-use crate::{database::user_repository::UserRepository, http_helpers, models::user::User, utils};
+use crate::{
+    accuracy::{self, AccuracyStats},
+    achievements::{self, BadgeDto},
+    config::{Config, JokerConfig, ScoreConfig},
+    database::{
+        leaderboard_history_repository::LeaderboardHistoryRepository,
+        league_repository::LeagueRepository, picture_meta_repository::PictureMetaRepository,
+        user_repository::UserRepository,
+    },
+    http_helpers,
+    leaderboard_history::{self, RankMovement},
+    models::leaderboard_snapshot::LeaderboardSnapshot,
+    ranking::{self, Standings},
+    routes, templates,
+    utils::{self, Day},
+};
 
-#[derive(Debug, Serialize)]
-struct LeaderboardUserEntry {
-    pub rank: String,
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LeaderboardUserEntry {
+    /// Rank among the players shown by default, `None` for hidden players
+    pub rank: Option<u32>,
+    /// Rank when hidden players are shown as well
+    pub rank_all: u32,
     pub username: String,
     pub guesses: usize,
     pub score: u32,
-    pub accuracy: Option<u32>,
+    pub accuracy: AccuracyStats,
     pub hidden: bool,
+    pub badges: Vec<BadgeDto>,
+    #[schema(value_type = Option<u32>)]
+    pub joker_day: Option<Day>,
+    pub movement: Option<RankMovement>,
 }
 
-fn get_leaderboard_users(users: &[User]) -> Vec<LeaderboardUserEntry> {
-    users
+pub fn get_leaderboard_users(
+    standings: &Standings,
+    locale: &str,
+    previous_snapshot: Option<&LeaderboardSnapshot>,
+) -> Result<Vec<LeaderboardUserEntry>> {
+    let visible_ranks: HashMap<_, _> = standings
+        .visible
+        .iter()
+        .map(|p| (&p.user.id, p.rank))
+        .collect();
+
+    let pictures = PictureMetaRepository::get_all_pictures()?;
+    standings
+        .all
         .iter()
-        .enumerate()
-        .map(|(rank, user)| {
-            let score = user.get_total_score().unwrap();
+        .map(|player| {
+            let user = &player.user;
+            let score = player.stats.score;
             let guesses = user.guess_data.len();
-            let accuracy = if guesses > 0 {
-                Some(score / guesses as u32)
-            } else {
-                None
+            let accuracy = AccuracyStats::compute(user, &pictures)?;
+            let rank = visible_ranks.get(&user.id).copied();
+
+            // movements are relative to the ranks shown by default, hidden players excluded
+            let movement = match (previous_snapshot, rank) {
+                (Some(snapshot), Some(rank)) => Some(leaderboard_history::get_rank_movement(
+                    snapshot.get_rank(&user.id),
+                    rank,
+                )),
+                _ => None,
             };
 
-            LeaderboardUserEntry {
-                rank: (rank + 1).to_string(),
+            Ok(LeaderboardUserEntry {
+                rank,
+                rank_all: player.rank,
                 username: user.username.to_owned(),
                 guesses,
                 accuracy,
                 score,
-                hidden: user.hidden || guesses == 0,
-            }
+                hidden: rank.is_none(),
+                badges: achievements::to_badges(&user.achievements, locale),
+                joker_day: user.get_joker_day(),
+                movement,
+            })
         })
-        .collect::<Vec<_>>()
+        .collect()
 }
 
-pub fn get_leaderboard(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
-    let mut users = UserRepository::get_all_users()?.to_vec();
-    users.sort_by_key(|u| cmp::Reverse(u.get_total_score().unwrap()));
+/// Best final score: a perfect guess every day, one of them doubled by the joker when enabled
+fn get_max_total_score(score: &ScoreConfig, joker: &JokerConfig) -> u32 {
+    let per_picture = score.max_reward as u32;
+    let joker_bonus = if joker.enabled {
+        per_picture * joker.multiplier.saturating_sub(1)
+    } else {
+        0
+    };
+    per_picture * 25 + joker_bonus
+}
+
+pub fn get_leaderboard(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    let league = match request.query.get("league") {
+        Some(league_id) => {
+            let user = http_helpers::get_logged_in_user(request)?;
+            match (user, LeagueRepository::get_league_by_id(league_id)?) {
+                (Some(user), Some(league)) if league.is_member(&user.id) => Some(league),
+                _ => {
+                    debug!("league leaderboard not accessible: {league_id}");
+                    return routes::catcher_get_404(request, routing_data);
+                }
+            }
+        }
+        None => None,
+    };
+
+    let standings = match &league {
+        Some(league) => {
+            let mut users = UserRepository::get_all_users()?;
+            users.retain(|u| league.is_member(&u.id));
+            Arc::new(ranking::compute_standings(users)?)
+        }
+        None => ranking::get_standings()?,
+    };
+
+    // rank movements only make sense against the global standings
+    let previous_snapshot = match league {
+        Some(_) => None,
+        None => LeaderboardHistoryRepository::get_latest_snapshot()?,
+    };
 
     let total_days = utils::get_current_day();
+    let user_locale = http_helpers::get_user_locale(request)?.as_str();
+    let config = Config::get()?;
     let data = json!({
+        "league_name": league.map(|l| l.name),
         "total_days": total_days,
-        "users": get_leaderboard_users(&users),
-        "i18n": I18n::from_request(request).unwrap()
+        "users": get_leaderboard_users(&standings, user_locale, previous_snapshot.as_ref())?,
+        "closeGuessMinutes": accuracy::CLOSE_GUESS_MINUTES,
+        "maxRewardPerPicture": config.score.max_reward as u32,
+        "maxTotalScore": get_max_total_score(&config.score, &config.joker),
     });
-    let rendered = utils::render_view("leaderboard", &data)?;
-    HttpResponseBuilder::new().set_html_body(&rendered).build()
+    let rendered = templates::render(request, "leaderboard", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
 }
 
-#[derive(Serialize)]
-struct I18n {
-    title: String,
-    rank: String,
-    user: String,
-    guesses: String,
-    score: String,
-    accuracy: String,
-    text_max_score: String,
-    check_point_system: String,
-    show_hidden_players: String,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ScoringCurve;
 
-impl I18n {
-    fn from_request(request: &HttpRequest) -> Result<I18n> {
-        let user_locale = http_helpers::get_user_locale(request)?.to_str();
-        Ok(I18n {
-            title: t!("leaderboard.title", locale = user_locale).to_string(),
-            rank: t!("leaderboard.rank", locale = user_locale).to_string(),
-            user: t!("leaderboard.user", locale = user_locale).to_string(),
-            guesses: t!("leaderboard.guesses", locale = user_locale).to_string(),
-            score: t!("leaderboard.score", locale = user_locale).to_string(),
-            accuracy: t!("leaderboard.accuracy", locale = user_locale).to_string(),
-            text_max_score: t!("leaderboard.text_max_score", locale = user_locale).to_string(),
-            check_point_system: t!("check_point_system", locale = user_locale).to_string(),
-            show_hidden_players: t!("leaderboard.show_hidden_players", locale = user_locale)
-                .to_string(),
-        })
+    #[test]
+    fn test_get_max_total_score() {
+        let score = ScoreConfig {
+            max_reward: 200.0,
+            curve: ScoringCurve::Linear { divider: 1440 },
+        };
+        let joker = JokerConfig {
+            enabled: true,
+            multiplier: 2,
+        };
+        assert_eq!(5200, get_max_total_score(&score, &joker));
+
+        let joker = JokerConfig {
+            enabled: false,
+            multiplier: 2,
+        };
+        assert_eq!(5000, get_max_total_score(&score, &joker));
     }
 }
@@ -0,0 +1,284 @@
+use anyhow::Result;
+use log::{debug, info};
+use rtfw_http::{
+    http::{HttpRequest, HttpResponse},
+    router::RoutingData,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    database::{league_repository::LeagueRepository, user_repository::UserRepository},
+    http_helpers::{self, bad_request, bad_request_msg, forbidden, unauthorized},
+    models::league::League,
+    routes, templates, utils,
+};
+
+const LEAGUE_ID_LENGTH: usize = 12;
+const INVITE_CODE_LENGTH: usize = 8;
+const MAX_LEAGUE_NAME_LENGTH: usize = 32;
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateLeagueRequest {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JoinLeagueRequest {
+    pub invite_code: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RemoveMemberRequest {
+    pub user_id: String,
+}
+
+#[derive(Debug, Serialize)]
+struct LeagueEntry {
+    pub id: String,
+    pub name: String,
+    pub invite_code: String,
+    pub members: usize,
+    pub is_admin: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct LeagueMemberEntry {
+    pub id: String,
+    pub username: String,
+    pub score: u32,
+    pub is_admin: bool,
+    pub is_owner: bool,
+    pub is_me: bool,
+}
+
+fn get_league_from_route(routing_data: &RoutingData) -> Result<Option<League>> {
+    let league_id: Option<String> = match routing_data.get_value("id") {
+        Ok(league_id) => league_id,
+        Err(e) => {
+            debug!("invalid league ID format: {e}");
+            return Ok(None);
+        }
+    };
+
+    match league_id {
+        Some(league_id) => LeagueRepository::get_league_by_id(&league_id),
+        None => Ok(None),
+    }
+}
+
+pub fn get_leagues(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+    let user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return http_helpers::redirect("/auth/login"),
+    };
+
+    let leagues: Vec<_> = LeagueRepository::get_leagues_of_user(&user.id)?
+        .into_iter()
+        .map(|league| LeagueEntry {
+            is_admin: league.is_admin(&user.id),
+            members: league.member_ids.len(),
+            id: league.id,
+            name: league.name,
+            invite_code: league.invite_code,
+        })
+        .collect();
+
+    let data = json!({
+        "leagues": leagues,
+    });
+    let rendered = templates::render(request, "leagues", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
+}
+
+pub fn get_league(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    let user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return http_helpers::redirect("/auth/login"),
+    };
+
+    let league = match get_league_from_route(routing_data)? {
+        Some(league) if league.is_member(&user.id) => league,
+        _ => return routes::catcher_get_404(request, routing_data),
+    };
+
+    let mut members: Vec<_> = UserRepository::get_all_users()?
+        .iter()
+        .filter(|u| league.is_member(&u.id))
+        .map(|member| -> Result<LeagueMemberEntry> {
+            Ok(LeagueMemberEntry {
+                id: member.id.to_owned(),
+                username: member.username.to_owned(),
+                score: member.get_total_score()?,
+                is_admin: league.is_admin(&member.id),
+                is_owner: league.owner_id == member.id,
+                is_me: member.id == user.id,
+            })
+        })
+        .collect::<Result<_>>()?;
+    members.sort_by(|a, b| a.username.cmp(&b.username));
+
+    let data = json!({
+        "league": {
+            "id": &league.id,
+            "name": &league.name,
+            "inviteCode": &league.invite_code,
+        },
+        "canManage": league.is_admin(&user.id),
+        "isOwner": league.owner_id == user.id,
+        "members": members,
+    });
+    let rendered = templates::render(request, "league", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
+}
+
+fn generate_unique_invite_code() -> Result<String> {
+    loop {
+        let invite_code = utils::generate_code(INVITE_CODE_LENGTH);
+        if LeagueRepository::get_league_by_invite_code(&invite_code)?.is_none() {
+            return Ok(invite_code);
+        }
+    }
+}
+
+pub fn post_create_league(
+    request: &HttpRequest,
+    _routing_data: &RoutingData,
+) -> Result<HttpResponse> {
+    let user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return unauthorized(),
+    };
+
+    let request_data = match http_helpers::get_json_body::<CreateLeagueRequest>(request) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!("failed to create league struct from json: {e}");
+            return bad_request();
+        }
+    };
+
+    let name = request_data.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_LEAGUE_NAME_LENGTH {
+        return bad_request_msg("Invalid league name!");
+    }
+
+    let league = League::new(
+        utils::generate_code(LEAGUE_ID_LENGTH).to_lowercase(),
+        name.to_string(),
+        generate_unique_invite_code()?,
+        &user.id,
+    );
+    let league_id = league.id.to_owned();
+
+    info!("user {} created league: {}", user.username, league.name);
+    LeagueRepository::create_league(league)?;
+
+    http_helpers::response()
+        .set_json_body(&json!({"id": league_id}))?
+        .build()
+}
+
+pub fn post_join_league(
+    request: &HttpRequest,
+    _routing_data: &RoutingData,
+) -> Result<HttpResponse> {
+    let user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return unauthorized(),
+    };
+
+    let request_data = match http_helpers::get_json_body::<JoinLeagueRequest>(request) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!("failed to create join league struct from json: {e}");
+            return bad_request();
+        }
+    };
+
+    let invite_code = request_data.invite_code.trim().to_uppercase();
+    let mut league = match LeagueRepository::get_league_by_invite_code(&invite_code)? {
+        Some(league) => league,
+        None => return bad_request_msg("Invalid invite code!"),
+    };
+
+    if league.is_member(&user.id) {
+        return bad_request_msg("You are already a member of this league!");
+    }
+
+    info!("user {} joined league: {}", user.username, league.name);
+    let league_id = league.id.to_owned();
+    league.member_ids.push(user.id);
+    LeagueRepository::update_league(league)?;
+
+    http_helpers::response()
+        .set_json_body(&json!({"id": league_id}))?
+        .build()
+}
+
+pub fn post_remove_member(
+    request: &HttpRequest,
+    routing_data: &RoutingData,
+) -> Result<HttpResponse> {
+    let user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return unauthorized(),
+    };
+
+    let mut league = match get_league_from_route(routing_data)? {
+        Some(league) if league.is_admin(&user.id) => league,
+        Some(_) => return forbidden(),
+        None => return bad_request(),
+    };
+
+    let request_data = match http_helpers::get_json_body::<RemoveMemberRequest>(request) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!("failed to create remove member struct from json: {e}");
+            return bad_request();
+        }
+    };
+
+    let member_id = request_data.user_id;
+    if !league.is_member(&member_id) {
+        return bad_request_msg("This player is not a member of this league!");
+    }
+
+    if member_id == league.owner_id {
+        return bad_request_msg("The owner of a league cannot be removed!");
+    }
+
+    info!(
+        "user {} removed member {member_id} from league: {}",
+        user.username, league.name
+    );
+    league.remove_member(&member_id);
+    LeagueRepository::update_league(league)?;
+
+    http_helpers::response().build()
+}
+
+pub fn post_leave_league(
+    request: &HttpRequest,
+    routing_data: &RoutingData,
+) -> Result<HttpResponse> {
+    let user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return unauthorized(),
+    };
+
+    let mut league = match get_league_from_route(routing_data)? {
+        Some(league) if league.is_member(&user.id) => league,
+        _ => return bad_request(),
+    };
+
+    if user.id == league.owner_id {
+        return bad_request_msg("The owner of a league cannot leave it!");
+    }
+
+    info!("user {} left league: {}", user.username, league.name);
+    league.remove_member(&user.id);
+    LeagueRepository::update_league(league)?;
+
+    http_helpers::response().build()
+}
@@ -1,5 +1,13 @@
+pub mod api;
+pub mod api_token;
+pub mod assets;
 pub mod auth;
+pub mod calendar;
 pub mod day;
+pub mod feed;
 pub mod guess;
 pub mod leaderboard;
+pub mod league;
 pub mod profile;
+pub mod scoring;
+pub mod team;
@@ -1,17 +1,22 @@
 use anyhow::Result;
 use rtfw_http::{
-    http::{HttpRequest, HttpResponse, HttpResponseBuilder},
+    http::{HttpRequest, HttpResponse},
     router::RoutingData,
 };
-use rust_i18n::t;
 use serde::Serialize;
 use serde_json::json;
 use std::cmp;
+use utoipa::ToSchema;
 
 use crate::{
-    database::picture_meta_repository::PictureMetaRepository,
-    http_helpers,
+    accuracy::{self, AccuracyStats},
+    achievements,
+    config::Config,
+    controllers::api_token,
+    database::{picture_meta_repository::PictureMetaRepository, team_repository::TeamRepository},
+    http_helpers, leaderboard_history,
     models::user::User,
+    ranking, templates,
     utils::{self, Day},
 };
 
@@ -21,27 +26,62 @@ pub fn get_me(request: &HttpRequest, _routing_data: &RoutingData) -> Result<Http
         None => return http_helpers::redirect("/auth/login"),
     };
 
+    let user_locale = http_helpers::get_user_locale(request)?.as_str();
+    let teams_config = Config::get()?.teams;
+    let team = match &user.team_id {
+        Some(team_id) => TeamRepository::get_team_by_id(team_id)?,
+        None => None,
+    };
+    let selectable_teams = if teams_config.enabled && teams_config.self_select && team.is_none() {
+        TeamRepository::get_all_teams()?
+    } else {
+        vec![]
+    };
+
+    let rank = ranking::get_standings()?
+        .visible
+        .iter()
+        .find(|p| p.user.id == user.id)
+        .map(|p| p.rank);
+
+    let pictures = PictureMetaRepository::get_all_pictures()?;
+    let accuracy = if user.guess_data.is_empty() {
+        None
+    } else {
+        Some(AccuracyStats::compute(&user, &pictures)?)
+    };
+
     let data = json!({
         "username": &user.username,
         "account_name": &user.oauth_username,
         "days": get_user_guess_days(&user),
         "total_score": user.get_total_score()?,
-        "i18n": I18n::from_request(request).unwrap(),
+        "rank": rank,
+        "accuracy": accuracy,
+        "closeGuessMinutes": accuracy::CLOSE_GUESS_MINUTES,
+        "badges": achievements::to_badges(&user.achievements, user_locale),
+        "teamsEnabled": teams_config.enabled,
+        "team": team,
+        "selectableTeams": selectable_teams,
+        "apiTokens": api_token::get_token_entries(&user.id)?,
+        "rankChart": leaderboard_history::build_rank_chart(&leaderboard_history::get_rank_history(&user.id)?),
     });
-    let rendered = utils::render_view("profile", &data)?;
-    HttpResponseBuilder::new().set_html_body(&rendered).build()
+    let rendered = templates::render(request, "profile", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
 }
 
-#[derive(Debug, Serialize)]
-struct UserGuessDay {
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserGuessDay {
+    #[schema(value_type = u32)]
     pub day: Day,
     pub guessed: bool,
     pub time: String,
     pub real_time: Option<String>,
     pub points: u32,
+    pub joker: bool,
 }
 
-fn get_user_guess_days(user: &User) -> Vec<UserGuessDay> {
+pub fn get_user_guess_days(user: &User) -> Vec<UserGuessDay> {
     let current_day = cmp::min(25, utils::get_current_day());
     (1..=current_day)
         .map(|d| {
@@ -52,6 +92,7 @@ fn get_user_guess_days(user: &User) -> Vec<UserGuessDay> {
                     time: String::new(),
                     real_time: None,
                     points: 0,
+                    joker: false,
                 },
                 |guess| UserGuessDay {
                     day: d,
@@ -64,40 +105,9 @@ fn get_user_guess_days(user: &User) -> Vec<UserGuessDay> {
                             .time_taken,
                     ),
                     points: user.get_points(d).unwrap(),
+                    joker: guess.joker,
                 },
             )
         })
         .collect()
 }
-
-#[derive(Serialize)]
-struct I18n {
-    title: String,
-    username: String,
-    generated_username: String,
-    account: String,
-    day: String,
-    guess: String,
-    real_time: String,
-    points: String,
-    score: String,
-    check_point_system: String,
-}
-
-impl I18n {
-    fn from_request(request: &HttpRequest) -> Result<I18n> {
-        let user_locale = http_helpers::get_user_locale(request)?.to_str();
-        Ok(I18n {
-            title: t!("profile.title", locale = user_locale).to_string(),
-            username: t!("profile.username", locale = user_locale).to_string(),
-            generated_username: t!("profile.generated_username", locale = user_locale).to_string(),
-            account: t!("profile.account", locale = user_locale).to_string(),
-            day: t!("profile.day", locale = user_locale).to_string(),
-            guess: t!("profile.guess", locale = user_locale).to_string(),
-            real_time: t!("profile.real_time", locale = user_locale).to_string(),
-            points: t!("profile.points", locale = user_locale).to_string(),
-            score: t!("profile.score", locale = user_locale).to_string(),
-            check_point_system: t!("check_point_system", locale = user_locale).to_string(),
-        })
-    }
-}
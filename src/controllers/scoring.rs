@@ -0,0 +1,48 @@
+use anyhow::Result;
+use rtfw_http::{
+    http::{HttpRequest, HttpResponse},
+    router::RoutingData,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    http_helpers,
+    scoring::{self, MINUTES_IN_DAY},
+    templates,
+};
+
+/// Differences (in minutes) displayed in the points table
+const SAMPLE_DIFFS: [u32; 17] = [
+    0, 1, 5, 10, 15, 30, 45, 60, 90, 120, 180, 240, 360, 480, 720, 960, 1200,
+];
+
+#[derive(Debug, Serialize)]
+struct ScoringRow {
+    pub diff: String,
+    pub minutes: u32,
+    pub points: u32,
+}
+
+fn get_scoring_rows() -> Result<Vec<ScoringRow>> {
+    let strategy = scoring::get_active_strategy()?;
+    Ok(SAMPLE_DIFFS
+        .iter()
+        .chain(std::iter::once(&(MINUTES_IN_DAY - 1)))
+        .map(|&minutes| ScoringRow {
+            diff: format!("{:02}:{:02}", minutes / 60, minutes % 60),
+            minutes,
+            points: strategy.points(minutes),
+        })
+        .collect())
+}
+
+pub fn get_scoring(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+    let strategy = scoring::get_active_strategy()?;
+    let data = json!({
+        "curve": strategy.name(),
+        "rows": get_scoring_rows()?,
+    });
+    let rendered = templates::render(request, "scoring", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
+}
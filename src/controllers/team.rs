@@ -0,0 +1,282 @@
+use anyhow::Result;
+use log::{debug, info};
+use rtfw_http::{
+    http::{HttpRequest, HttpResponse},
+    router::RoutingData,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::cmp;
+
+use crate::{
+    config::Config,
+    database::{team_repository::TeamRepository, user_repository::UserRepository},
+    http_helpers::{self, bad_request, bad_request_msg, forbidden, unauthorized},
+    models::{
+        team::{self, Team},
+        user::User,
+    },
+    ranking, routes, templates,
+    utils::{self, Day},
+};
+
+const TEAM_ID_LENGTH: usize = 12;
+const MAX_TEAM_NAME_LENGTH: usize = 32;
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateTeamRequest {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AssignMemberRequest {
+    pub username: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TeamLeaderboardEntry {
+    pub rank: String,
+    pub id: String,
+    pub name: String,
+    pub members: usize,
+    pub score: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct TeamMemberEntry {
+    pub username: String,
+    pub days: Vec<Option<u32>>,
+    pub score: u32,
+}
+
+pub fn is_team_admin(user: &User) -> Result<bool> {
+    let config = Config::get()?;
+    Ok(config.teams.admins.contains(&user.id))
+}
+
+fn get_team_from_route(routing_data: &RoutingData) -> Result<Option<Team>> {
+    let team_id: Option<String> = match routing_data.get_value("id") {
+        Ok(team_id) => team_id,
+        Err(e) => {
+            debug!("invalid team ID format: {e}");
+            return Ok(None);
+        }
+    };
+
+    match team_id {
+        Some(team_id) => TeamRepository::get_team_by_id(&team_id),
+        None => Ok(None),
+    }
+}
+
+pub fn get_team_leaderboard(users: &[User]) -> Result<Vec<TeamLeaderboardEntry>> {
+    let aggregation = Config::get()?.teams.aggregation;
+    let mut teams = TeamRepository::get_all_teams()?
+        .into_iter()
+        .map(|team| -> Result<TeamLeaderboardEntry> {
+            let scores = users
+                .iter()
+                .filter(|u| u.team_id.as_ref() == Some(&team.id))
+                .map(|u| u.get_total_score())
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(TeamLeaderboardEntry {
+                rank: String::new(),
+                members: scores.len(),
+                score: team::aggregate_scores(scores, aggregation),
+                id: team.id,
+                name: team.name,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    teams.sort_by_key(|t| cmp::Reverse(t.score));
+    let ranks = ranking::competition_ranks(&teams, |a, b| a.score == b.score);
+    for (team, rank) in teams.iter_mut().zip(ranks) {
+        team.rank = rank.to_string();
+    }
+    Ok(teams)
+}
+
+pub fn get_teams(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    if !Config::get()?.teams.enabled {
+        return routes::catcher_get_404(request, routing_data);
+    }
+
+    let user = http_helpers::get_logged_in_user(request)?;
+    let is_admin = match &user {
+        Some(user) => is_team_admin(user)?,
+        None => false,
+    };
+
+    let users = UserRepository::get_all_users()?;
+    let data = json!({
+        "teams": get_team_leaderboard(&users)?,
+        "isAdmin": is_admin,
+    });
+    let rendered = templates::render(request, "teams", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
+}
+
+pub fn get_team(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    if !Config::get()?.teams.enabled {
+        return routes::catcher_get_404(request, routing_data);
+    }
+
+    let team = match get_team_from_route(routing_data)? {
+        Some(team) => team,
+        None => return routes::catcher_get_404(request, routing_data),
+    };
+
+    let is_admin = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => is_team_admin(&user)?,
+        None => false,
+    };
+
+    let current_day = cmp::min(25, utils::get_current_day());
+    let days: Vec<Day> = (1..=current_day).collect();
+
+    let mut members = UserRepository::get_all_users()?
+        .iter()
+        .filter(|u| u.team_id.as_ref() == Some(&team.id))
+        .map(|member| -> Result<TeamMemberEntry> {
+            let points = days
+                .iter()
+                .map(|&day| {
+                    if member.has_guessed(day) {
+                        member.get_points(day).map(Some)
+                    } else {
+                        Ok(None)
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(TeamMemberEntry {
+                username: member.username.to_owned(),
+                days: points,
+                score: member.get_total_score()?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    members.sort_by_key(|m| cmp::Reverse(m.score));
+
+    let data = json!({
+        "team": {
+            "id": &team.id,
+            "name": &team.name,
+        },
+        "days": days,
+        "members": members,
+        "isAdmin": is_admin,
+    });
+    let rendered = templates::render(request, "team", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
+}
+
+pub fn post_create_team(
+    request: &HttpRequest,
+    _routing_data: &RoutingData,
+) -> Result<HttpResponse> {
+    let user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return unauthorized(),
+    };
+
+    if !Config::get()?.teams.enabled || !is_team_admin(&user)? {
+        return forbidden();
+    }
+
+    let request_data = match http_helpers::get_json_body::<CreateTeamRequest>(request) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!("failed to create team struct from json: {e}");
+            return bad_request();
+        }
+    };
+
+    let name = request_data.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_TEAM_NAME_LENGTH {
+        return bad_request_msg("Invalid team name!");
+    }
+
+    let team = Team::new(
+        utils::generate_code(TEAM_ID_LENGTH).to_lowercase(),
+        name.to_string(),
+    );
+    let team_id = team.id.to_owned();
+
+    info!("user {} created team: {}", user.username, team.name);
+    TeamRepository::create_team(team)?;
+
+    http_helpers::response()
+        .set_json_body(&json!({"id": team_id}))?
+        .build()
+}
+
+pub fn post_assign_member(
+    request: &HttpRequest,
+    routing_data: &RoutingData,
+) -> Result<HttpResponse> {
+    let user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return unauthorized(),
+    };
+
+    if !Config::get()?.teams.enabled || !is_team_admin(&user)? {
+        return forbidden();
+    }
+
+    let team = match get_team_from_route(routing_data)? {
+        Some(team) => team,
+        None => return bad_request(),
+    };
+
+    let request_data = match http_helpers::get_json_body::<AssignMemberRequest>(request) {
+        Ok(value) => value,
+        Err(e) => {
+            debug!("failed to create assign member struct from json: {e}");
+            return bad_request();
+        }
+    };
+
+    let mut member = match UserRepository::get_user_by_username(request_data.username.trim())? {
+        Some(member) => member,
+        None => return bad_request_msg("This player does not exist!"),
+    };
+
+    info!(
+        "user {} assigned {} to team: {}",
+        user.username, member.username, team.name
+    );
+    member.team_id = Some(team.id);
+    UserRepository::update_user(member)?;
+
+    http_helpers::response().build()
+}
+
+pub fn post_join_team(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    let mut user = match http_helpers::get_logged_in_user(request)? {
+        Some(user) => user,
+        None => return unauthorized(),
+    };
+
+    let config = Config::get()?;
+    if !config.teams.enabled || !config.teams.self_select {
+        return forbidden();
+    }
+
+    if user.team_id.is_some() {
+        return bad_request_msg("You are already part of a team!");
+    }
+
+    let team = match get_team_from_route(routing_data)? {
+        Some(team) => team,
+        None => return bad_request(),
+    };
+
+    info!("user {} joined team: {}", user.username, team.name);
+    user.team_id = Some(team.id);
+    UserRepository::update_user(user)?;
+
+    http_helpers::response().build()
+}
@@ -0,0 +1,76 @@
+use anyhow::{Result, bail};
+use log::debug;
+use std::{fs, path::Path};
+
+use crate::models::api_token::ApiToken;
+
+const DB_FILE_PATH: &str = "data/api_tokens.json";
+
+pub struct ApiTokenRepository;
+
+impl ApiTokenRepository {
+    pub fn initialize_database() -> Result<()> {
+        if !Path::new(DB_FILE_PATH).exists() {
+            fs::write(DB_FILE_PATH, "[]")?;
+        }
+        Ok(())
+    }
+
+    fn write_changes_to_database(tokens: &[ApiToken]) -> Result<()> {
+        let json = serde_json::to_string(tokens)?;
+        fs::write(DB_FILE_PATH, json)?;
+        Ok(())
+    }
+
+    pub fn get_token_by_hash(token_hash: &str) -> Result<Option<ApiToken>> {
+        Ok(Self::get_all_tokens()?
+            .iter()
+            .find(|t| t.token_hash == token_hash)
+            .cloned())
+    }
+
+    pub fn get_tokens_of_user(user_id: &str) -> Result<Vec<ApiToken>> {
+        Ok(Self::get_all_tokens()?
+            .into_iter()
+            .filter(|t| t.user_id == user_id)
+            .collect())
+    }
+
+    pub fn get_all_tokens() -> Result<Vec<ApiToken>> {
+        let tokens_raw = fs::read_to_string(DB_FILE_PATH)?;
+        let tokens = serde_json::from_str::<Vec<ApiToken>>(&tokens_raw)?;
+        Ok(tokens)
+    }
+
+    pub fn create_token(token: ApiToken) -> Result<()> {
+        let mut all_tokens = Self::get_all_tokens()?;
+        if let Some(existing_token) = all_tokens
+            .iter()
+            .find(|t| t.id == token.id || t.token_hash == token.token_hash)
+        {
+            bail!("api token with ID `{}` already exists", existing_token.id);
+        }
+
+        debug!("created api token {} for user {}", token.id, token.user_id);
+        all_tokens.push(token);
+        Self::write_changes_to_database(&all_tokens)
+    }
+
+    pub fn update_token(token: ApiToken) -> Result<()> {
+        let mut all_tokens = Self::get_all_tokens()?;
+        if !all_tokens.iter().any(|t| t.id == token.id) {
+            bail!("Api token does not exist: {}", token.id);
+        }
+
+        all_tokens.retain(|t| t.id != token.id);
+        all_tokens.push(token);
+        Self::write_changes_to_database(&all_tokens)
+    }
+
+    pub fn delete_token(token: &ApiToken) -> Result<()> {
+        let mut all_tokens = Self::get_all_tokens()?;
+        all_tokens.retain(|t| t.id != token.id);
+        debug!("deleted api token {} of user {}", token.id, token.user_id);
+        Self::write_changes_to_database(&all_tokens)
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::{Result, bail};
+use log::debug;
+use std::{fs, path::Path};
+
+use crate::{models::leaderboard_snapshot::LeaderboardSnapshot, utils::Day};
+
+const DB_FILE_PATH: &str = "data/leaderboard_history.json";
+
+pub struct LeaderboardHistoryRepository;
+
+impl LeaderboardHistoryRepository {
+    pub fn initialize_database() -> Result<()> {
+        if !Path::new(DB_FILE_PATH).exists() {
+            fs::write(DB_FILE_PATH, "[]")?;
+        }
+        Ok(())
+    }
+
+    fn write_changes_to_database(snapshots: &[LeaderboardSnapshot]) -> Result<()> {
+        let json = serde_json::to_string(snapshots)?;
+        fs::write(DB_FILE_PATH, json)?;
+        Ok(())
+    }
+
+    pub fn get_snapshot(day: Day) -> Result<Option<LeaderboardSnapshot>> {
+        Ok(Self::get_all_snapshots()?
+            .iter()
+            .find(|s| s.day == day)
+            .cloned())
+    }
+
+    pub fn get_latest_snapshot() -> Result<Option<LeaderboardSnapshot>> {
+        Ok(Self::get_all_snapshots()?.into_iter().max_by_key(|s| s.day))
+    }
+
+    /// Returns all the snapshots, ordered by day
+    pub fn get_all_snapshots() -> Result<Vec<LeaderboardSnapshot>> {
+        let snapshots_raw = fs::read_to_string(DB_FILE_PATH)?;
+        let mut snapshots = serde_json::from_str::<Vec<LeaderboardSnapshot>>(&snapshots_raw)?;
+        snapshots.sort_by_key(|s| s.day);
+        Ok(snapshots)
+    }
+
+    pub fn create_snapshot(snapshot: LeaderboardSnapshot) -> Result<()> {
+        let mut all_snapshots = Self::get_all_snapshots()?;
+        if all_snapshots.iter().any(|s| s.day == snapshot.day) {
+            bail!("snapshot for day `{}` already exists", snapshot.day);
+        }
+
+        debug!(
+            "created leaderboard snapshot for day {} ({} entries)",
+            snapshot.day,
+            snapshot.entries.len()
+        );
+        all_snapshots.push(snapshot);
+        Self::write_changes_to_database(&all_snapshots)
+    }
+}
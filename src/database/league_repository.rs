@@ -0,0 +1,81 @@
+use anyhow::{Result, bail};
+use log::debug;
+use std::{fs, path::Path};
+
+use crate::models::league::League;
+
+const DB_FILE_PATH: &str = "data/leagues.json";
+
+pub struct LeagueRepository;
+
+impl LeagueRepository {
+    pub fn initialize_database() -> Result<()> {
+        if !Path::new(DB_FILE_PATH).exists() {
+            fs::write(DB_FILE_PATH, "[]")?;
+        }
+        Ok(())
+    }
+
+    fn write_changes_to_database(leagues: &[League]) -> Result<()> {
+        let json = serde_json::to_string(leagues)?;
+        fs::write(DB_FILE_PATH, json)?;
+        Ok(())
+    }
+
+    pub fn get_league_by_id(id: &str) -> Result<Option<League>> {
+        Ok(Self::get_all_leagues()?
+            .iter()
+            .find(|l| l.id == id)
+            .cloned())
+    }
+
+    pub fn get_league_by_invite_code(invite_code: &str) -> Result<Option<League>> {
+        Ok(Self::get_all_leagues()?
+            .iter()
+            .find(|l| l.invite_code == invite_code)
+            .cloned())
+    }
+
+    pub fn get_leagues_of_user(user_id: &str) -> Result<Vec<League>> {
+        Ok(Self::get_all_leagues()?
+            .into_iter()
+            .filter(|l| l.is_member(user_id))
+            .collect())
+    }
+
+    pub fn get_all_leagues() -> Result<Vec<League>> {
+        let leagues_raw = fs::read_to_string(DB_FILE_PATH)?;
+        let leagues = serde_json::from_str::<Vec<League>>(&leagues_raw)?;
+        Ok(leagues)
+    }
+
+    pub fn create_league(league: League) -> Result<()> {
+        let mut all_leagues = Self::get_all_leagues()?;
+        if let Some(existing_league) = all_leagues
+            .iter()
+            .find(|l| l.id == league.id || l.invite_code == league.invite_code)
+        {
+            bail!(
+                "league with ID `{}` already exists: {:?}",
+                existing_league.id,
+                existing_league
+            );
+        }
+
+        debug!("created league: {:?}", league);
+        all_leagues.push(league);
+        Self::write_changes_to_database(&all_leagues)
+    }
+
+    pub fn update_league(league: League) -> Result<()> {
+        let mut all_leagues = Self::get_all_leagues()?;
+        if !all_leagues.iter().any(|l| l.id == league.id) {
+            bail!("League does not exist: {:?}", league);
+        }
+
+        debug!("updated league: {:?}", league);
+        all_leagues.retain(|l| l.id != league.id);
+        all_leagues.push(league);
+        Self::write_changes_to_database(&all_leagues)
+    }
+}
@@ -1,2 +1,7 @@
+pub mod api_token_repository;
+pub mod leaderboard_history_repository;
+pub mod league_repository;
 pub mod picture_meta_repository;
+pub mod score_cache_repository;
+pub mod team_repository;
 pub mod user_repository;
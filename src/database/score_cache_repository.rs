@@ -0,0 +1,47 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+const DB_FILE_PATH: &str = "data/score_cache.json";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ScoreCacheState {
+    /// Fingerprint of the scoring inputs the cached points were computed with
+    pub fingerprint: String,
+    pub computed_at: DateTime<Utc>,
+}
+
+pub struct ScoreCacheRepository;
+
+impl ScoreCacheRepository {
+    pub fn initialize_database() -> Result<()> {
+        if !Path::new(DB_FILE_PATH).exists() {
+            fs::write(DB_FILE_PATH, "null")?;
+        }
+        Ok(())
+    }
+
+    fn write_changes_to_database(state: Option<&ScoreCacheState>) -> Result<()> {
+        let json = serde_json::to_string(&state)?;
+        fs::write(DB_FILE_PATH, json)?;
+        Ok(())
+    }
+
+    pub fn get_state() -> Result<Option<ScoreCacheState>> {
+        let state_raw = fs::read_to_string(DB_FILE_PATH)?;
+        let state = serde_json::from_str::<Option<ScoreCacheState>>(&state_raw)?;
+        Ok(state)
+    }
+
+    pub fn set_state(state: ScoreCacheState) -> Result<()> {
+        debug!("updated score cache state: {:?}", state);
+        Self::write_changes_to_database(Some(&state))
+    }
+
+    pub fn clear_state() -> Result<()> {
+        debug!("cleared score cache state");
+        Self::write_changes_to_database(None)
+    }
+}
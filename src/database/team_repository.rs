@@ -0,0 +1,52 @@
+use anyhow::{Result, bail};
+use log::debug;
+use std::{fs, path::Path};
+
+use crate::models::team::Team;
+
+const DB_FILE_PATH: &str = "data/teams.json";
+
+pub struct TeamRepository;
+
+impl TeamRepository {
+    pub fn initialize_database() -> Result<()> {
+        if !Path::new(DB_FILE_PATH).exists() {
+            fs::write(DB_FILE_PATH, "[]")?;
+        }
+        Ok(())
+    }
+
+    fn write_changes_to_database(teams: &[Team]) -> Result<()> {
+        let json = serde_json::to_string(teams)?;
+        fs::write(DB_FILE_PATH, json)?;
+        Ok(())
+    }
+
+    pub fn get_team_by_id(id: &str) -> Result<Option<Team>> {
+        Ok(Self::get_all_teams()?.iter().find(|t| t.id == id).cloned())
+    }
+
+    pub fn get_all_teams() -> Result<Vec<Team>> {
+        let teams_raw = fs::read_to_string(DB_FILE_PATH)?;
+        let teams = serde_json::from_str::<Vec<Team>>(&teams_raw)?;
+        Ok(teams)
+    }
+
+    pub fn create_team(team: Team) -> Result<()> {
+        let mut all_teams = Self::get_all_teams()?;
+        if let Some(existing_team) = all_teams
+            .iter()
+            .find(|t| t.id == team.id || t.name == team.name)
+        {
+            bail!(
+                "team with ID `{}` already exists: {:?}",
+                existing_team.id,
+                existing_team
+            );
+        }
+
+        debug!("created team: {:?}", team);
+        all_teams.push(team);
+        Self::write_changes_to_database(&all_teams)
+    }
+}
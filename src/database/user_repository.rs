@@ -2,7 +2,7 @@ use anyhow::{Result, bail};
 use log::debug;
 use std::{fs, path::Path};
 
-use crate::models::user::User;
+use crate::{models::user::User, ranking};
 
 const DB_FILE_PATH: &str = "data/users.json";
 
@@ -19,6 +19,7 @@ impl UserRepository {
     fn write_changes_to_database(users: &[User]) -> Result<()> {
         let json = serde_json::to_string(users)?;
         fs::write(DB_FILE_PATH, json)?;
+        ranking::invalidate_standings();
         Ok(())
     }
 
@@ -73,6 +74,12 @@ impl UserRepository {
         Self::write_changes_to_database(&all_users)
     }
 
+    /// Replaces every user at once, used for bulk updates such as score recomputes
+    pub fn update_all_users(users: Vec<User>) -> Result<()> {
+        debug!("updated all users ({} users)", users.len());
+        Self::write_changes_to_database(&users)
+    }
+
     pub fn delete_user(user: &User) -> Result<()> {
         let mut all_users = Self::get_all_users()?;
         all_users.retain(|u| u.id != user.id);
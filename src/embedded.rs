@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::{borrow::Cow, collections::HashMap, fs, sync::LazyLock};
+
+use crate::{config::Config, locale::Locale};
+
+/// Views, styles, scripts and other assets, generated by `build.rs` and keyed by their path
+/// relative to the root of the project
+static EMBEDDED_FILES: &[(&str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/embedded_files.rs"));
+
+static CONTENT_HASHES: LazyLock<HashMap<&'static str, String>> = LazyLock::new(|| {
+    EMBEDDED_FILES
+        .iter()
+        .map(|(path, content)| (*path, get_content_hash(content)))
+        .collect()
+});
+
+/// In dev mode, files are read from the disk so that edits show up without a rebuild
+pub fn is_reading_from_disk() -> bool {
+    Config::get().is_ok_and(|config| config.dev_mode)
+}
+
+pub fn read(path: &str) -> Result<Cow<'static, [u8]>> {
+    if is_reading_from_disk() {
+        let content = fs::read(path).with_context(|| format!("failed to read {path}"))?;
+        return Ok(Cow::Owned(content));
+    }
+
+    EMBEDDED_FILES
+        .iter()
+        .find(|(embedded_path, _)| *embedded_path == path)
+        .map(|(_, content)| Cow::Borrowed(*content))
+        .with_context(|| format!("{path} is not embedded in the binary"))
+}
+
+pub fn read_to_string(path: &str) -> Result<String> {
+    let content = read(path)?;
+    let content = String::from_utf8(content.into_owned())
+        .with_context(|| format!("{path} is not valid UTF-8"))?;
+    Ok(content)
+}
+
+/// Paths of the embedded files found under the given directory, subdirectories included
+pub fn list(dir: &str) -> impl Iterator<Item = &'static str> {
+    let prefix = format!("{}/", dir.trim_end_matches('/'));
+    EMBEDDED_FILES
+        .iter()
+        .map(|(path, _)| *path)
+        .filter(move |path| path.starts_with(&prefix))
+}
+
+/// Path of the variant of a file for the given locale, like `about_fr.md`, when there is one.
+/// The fallback chain of the locale is followed, so `fr-CA` also finds `about_fr.md`.
+pub fn localized_path(path: &str, locale: Locale) -> String {
+    locale
+        .fallback_chain()
+        .into_iter()
+        .map(|code| match path.rsplit_once('.') {
+            Some((stem, extension)) => format!("{stem}_{code}.{extension}"),
+            None => format!("{path}_{code}"),
+        })
+        .find(|localized_path| {
+            EMBEDDED_FILES
+                .iter()
+                .any(|(path, _)| path == localized_path)
+        })
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Short hash of the embedded content, changes whenever the file does
+pub fn content_hash(path: &str) -> Option<&'static str> {
+    CONTENT_HASHES.get(path).map(String::as_str)
+}
+
+fn get_content_hash(content: &[u8]) -> String {
+    let digest = Sha256::digest(content);
+    digest[..4]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_files_are_embedded() {
+        for path in [
+            "src/views/index.hbs",
+            "src/views/layouts/base.hbs",
+            "src/styles/main.css",
+            "src/scripts/day.js",
+            "src/assets/favicon.ico",
+            "src/assets/about.md",
+            "data/dictionaries/nouns.txt",
+        ] {
+            let content = read(path).unwrap();
+            assert_eq!(fs::read(path).unwrap(), content.as_ref(), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_list() {
+        let scripts: Vec<_> = list("src/scripts").collect();
+        assert!(scripts.contains(&"src/scripts/day.js"));
+        assert!(scripts.iter().all(|path| path.starts_with("src/scripts/")));
+        assert!(list("src/views").any(|path| path == "src/views/partials/nav.hbs"));
+    }
+
+    #[test]
+    fn test_localized_path() {
+        let french = Locale::from_code("fr").unwrap();
+        assert_eq!(
+            "src/assets/about_fr.md",
+            localized_path("src/assets/about.md", french)
+        );
+        assert_eq!(
+            "src/assets/404_phrases_fr.md",
+            localized_path("src/assets/404_phrases.md", french)
+        );
+        assert_eq!(
+            "src/assets/about.md",
+            localized_path("src/assets/about.md", Locale::default())
+        );
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let hash = content_hash("src/styles/main.css").unwrap();
+        assert_eq!(8, hash.len());
+        assert_eq!(
+            get_content_hash(&fs::read("src/styles/main.css").unwrap()),
+            hash
+        );
+        assert_ne!(hash, content_hash("src/styles/day.css").unwrap());
+        assert_eq!(None, content_hash("src/styles/missing.css"));
+    }
+}
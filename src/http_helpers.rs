@@ -1,116 +1,225 @@
-use anyhow::{Context, Result};
-use chrono::{DateTime, TimeZone, Utc};
-use log::trace;
+use anyhow::{Result, bail};
+use log::{trace, warn};
 use rtfw_http::http::{
-    HttpCookie, HttpRequest, HttpResponse, HttpResponseBuilder,
+    HttpMethod, HttpRequest, HttpResponse, HttpResponseBuilder,
     response_status_codes::HttpStatusCode,
 };
+use serde::de::DeserializeOwned;
+use std::time::Duration;
 
 use crate::{
     database::user_repository::UserRepository,
+    locale::Locale,
     models::{oauth2_response::OAuth2Response, user::User},
-    oauth2, security,
+    oauth2, security, security_headers,
 };
 
 pub const BEARER_COOKIE: &str = "aot-bearer";
+pub const LOCALE_COOKIE: &str = "aot-locale";
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+const CSRF_FORM_FIELD: &str = "_csrf";
 
+/// Locale of the page: the language picked with the switcher, then the preference saved by a
+/// logged in user, then the best supported language of the browser, and English otherwise
 pub fn get_user_locale(request: &HttpRequest) -> Result<Locale> {
-    let acc_lang_value = request
-        .headers
-        .get("Accept-Language")
-        .map(|h| h.value.to_owned())
-        .unwrap_or_default();
+    if let Some(locale) = request
+        .cookies
+        .get(LOCALE_COOKIE)
+        .and_then(|cookie| Locale::from_code(&cookie.value))
+    {
+        return Ok(locale);
+    }
 
-    let locale = parse_accept_language_query(&acc_lang_value)?
-        .first()
-        .cloned()
-        .context("there should be one")?;
+    if let Some(bearer) = request.cookies.get(BEARER_COOKIE)
+        && let Some(user) = UserRepository::get_user_by_bearer(&bearer.value)?
+        && let Some(locale) = user.preferred_locale.as_deref().and_then(Locale::from_code)
+    {
+        return Ok(locale);
+    }
 
+    let locale = get_header(request, "Accept-Language")
+        .and_then(|value| parse_accept_language(value).first().copied())
+        .unwrap_or_default();
     Ok(locale)
 }
 
-fn parse_accept_language_query(value: &str) -> Result<Vec<Locale>> {
-    Ok(value
+/// Supported locales of an `Accept-Language` header, by decreasing weight.
+/// Malformed entries are skipped, as are the ones refused with `q=0`.
+/// A region falls back to its language, `fr-CH` gives `fr`.
+fn parse_accept_language(value: &str) -> Vec<Locale> {
+    let mut locales: Vec<_> = value
         .split(',')
-        .map(|v| parse_lang_declaration(v.trim()))
-        .collect::<Vec<_>>())
-}
-
-fn parse_lang_declaration(value: &str) -> Locale {
-    if let Some((lang, _weight)) = value.split_once(';') {
-        Locale::from_language_tag(lang)
-    } else {
-        Locale::from_language_tag(value)
-    }
-}
+        .filter_map(|declaration| {
+            let mut parts = declaration.split(';');
+            let locale = Locale::from_language_tag(parts.next()?.trim())?;
+            let weight = match parts.find_map(|param| param.trim().strip_prefix("q=")) {
+                Some(weight) => weight.trim().parse::<f32>().ok()?,
+                None => 1.0,
+            };
+            (weight > 0.0 && weight <= 1.0).then_some((locale, weight))
+        })
+        .collect();
 
-#[derive(Clone)]
-pub enum Locale {
-    English,
-    French,
+    // the sort is stable, so the order of the header breaks ties
+    locales.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    locales.into_iter().map(|(locale, _)| locale).collect()
 }
 
-impl Locale {
-    pub fn from_language_tag(value: &str) -> Locale {
-        let identifier = if let Some((identifier, _region)) = value.split_once('-') {
-            identifier
-        } else {
-            value
-        };
-
-        match identifier {
-            "fr" => Self::French,
-            _ => Self::English,
-        }
-    }
-
-    pub fn to_str(&self) -> String {
-        match self {
-            Locale::English => "en".to_owned(),
-            Locale::French => "fr".to_owned(),
-        }
-    }
+/// Every response starts here, so that it carries the security headers
+pub fn response() -> HttpResponseBuilder {
+    security_headers::apply(HttpResponseBuilder::new())
 }
 
 pub fn redirect(location: &str) -> Result<HttpResponse> {
-    HttpResponseBuilder::new()
+    response()
         .set_status(HttpStatusCode::Found)
         .set_header("Location", location)
         .build()
 }
 
 pub fn bad_request() -> Result<HttpResponse> {
-    HttpResponseBuilder::new()
-        .set_status(HttpStatusCode::BadRequest)
-        .build()
+    response().set_status(HttpStatusCode::BadRequest).build()
 }
 
 pub fn bad_request_msg(message: &str) -> Result<HttpResponse> {
-    HttpResponseBuilder::new()
+    response()
         .set_status(HttpStatusCode::BadRequest)
         .set_html_body(message)
         .build()
 }
 
-pub fn create_bearer_cookie(oauth2_response: &OAuth2Response) -> HttpCookie {
-    HttpCookie::new(BEARER_COOKIE, &oauth2_response.access_token)
-        .set_path(Some("/"))
-        .set_http_only(true)
-        .set_max_age(oauth2_response.expires_in.and_then(|num| {
-            if num <= i32::MAX as u64 {
-                Some(num as i32)
-            } else {
-                None
-            }
-        }))
+pub fn unauthorized() -> Result<HttpResponse> {
+    response().set_status(HttpStatusCode::Unauthorized).build()
+}
+
+pub fn forbidden() -> Result<HttpResponse> {
+    response().set_status(HttpStatusCode::Forbidden).build()
+}
+
+/// Value of the `Retry-After` header, in whole seconds
+pub fn retry_after_secs(retry_after: Duration) -> String {
+    retry_after.as_secs_f64().ceil().max(1.0).to_string()
+}
+
+pub fn too_many_requests(retry_after: Duration) -> Result<HttpResponse> {
+    response()
+        .set_status(HttpStatusCode::TooManyRequests)
+        .set_header("Retry-After", &retry_after_secs(retry_after))
+        .set_html_body("Too many requests, please slow down!")
+        .build()
+}
+
+pub fn get_json_body<T: DeserializeOwned>(request: &HttpRequest) -> Result<T> {
+    let body = request.get_str_body()?;
+    match serde_json::from_str::<T>(&body) {
+        Ok(value) => Ok(value),
+        Err(e) => {
+            trace!("invalid json body: {:?}", &body);
+            bail!("failed to parse json body: {e}")
+        }
+    }
+}
+
+/// Value of the `Set-Cookie` header of the session. It is written by hand as the bearer has to be
+/// `SameSite=Lax`, so that other sites cannot send requests with it
+pub fn create_bearer_cookie(oauth2_response: &OAuth2Response) -> String {
+    let max_age = oauth2_response
+        .expires_in
+        .filter(|&num| num <= i32::MAX as u64)
+        .map(|num| format!("; Max-Age={num}"))
+        .unwrap_or_default();
+    format!(
+        "{BEARER_COOKIE}={}; Path=/; HttpOnly; SameSite=Lax{max_age}",
+        oauth2_response.access_token
+    )
+}
+
+pub fn create_clear_bearer_cookie() -> String {
+    format!(
+        "{BEARER_COOKIE}=; Path=/; HttpOnly; SameSite=Lax; Expires=Thu, 01 Jan 1970 00:00:00 GMT"
+    )
+}
+
+/// Value of the `Set-Cookie` header remembering the language picked with the switcher
+pub fn create_locale_cookie(locale: Locale) -> String {
+    format!(
+        "{LOCALE_COOKIE}={}; Path=/; HttpOnly; SameSite=Lax; Max-Age=31536000",
+        locale.as_str()
+    )
+}
+
+/// Header names are matched case-insensitively, proxies tend to rewrite them
+pub fn get_header<'a>(request: &'a HttpRequest, name: &str) -> Option<&'a str> {
+    let headers = request
+        .headers
+        .iter()
+        .map(|(key, header)| (key.as_str(), header.value.as_str()));
+    find_header(headers, name)
+}
+
+fn find_header<'a>(
+    headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+    name: &str,
+) -> Option<&'a str> {
+    headers
+        .into_iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+/// Token to embed in the pages of a logged in user, see [`security::get_csrf_token`]
+pub fn get_csrf_token(request: &HttpRequest) -> Option<String> {
+    request
+        .cookies
+        .get(BEARER_COOKIE)
+        .map(|bearer| security::get_csrf_token(&bearer.value))
+}
+
+/// The token is sent in the `X-CSRF-Token` header by scripts and in the `_csrf` field by forms
+fn has_valid_csrf_token(request: &HttpRequest, bearer: &str) -> bool {
+    let expected = security::get_csrf_token(bearer);
+    if let Some(token) = get_header(request, CSRF_HEADER) {
+        return token == expected;
+    }
+
+    get_form_csrf_token(request).is_some_and(|token| token == expected)
+}
+
+fn get_form_csrf_token(request: &HttpRequest) -> Option<String> {
+    let is_form = get_header(request, "Content-Type")
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+    if !is_form {
+        return None;
+    }
+
+    url::form_urlencoded::parse(&request.body)
+        .find(|(key, _)| key == CSRF_FORM_FIELD)
+        .map(|(_, token)| token.into_owned())
+}
+
+/// For forms that visitors can post without being logged in, like the language switcher: the
+/// token is required along with the session cookie, and rejected when sent without it as tokens
+/// are derived from the session
+pub fn has_valid_optional_csrf_token(request: &HttpRequest) -> bool {
+    match request.cookies.get(BEARER_COOKIE) {
+        Some(bearer) => has_valid_csrf_token(request, &bearer.value),
+        None => {
+            get_header(request, CSRF_HEADER).is_none() && get_form_csrf_token(request).is_none()
+        }
+    }
+}
+
+/// Token sent in an `Authorization: Bearer <token>` header, used by API clients
+pub fn get_authorization_bearer(request: &HttpRequest) -> Option<String> {
+    parse_bearer_token(get_header(request, "Authorization")?)
 }
 
-pub fn create_clear_bearer_cookie() -> HttpCookie {
-    let expired_date: Option<DateTime<Utc>> = Utc.timestamp_opt(0, 0).single();
-    HttpCookie::new(BEARER_COOKIE, "")
-        .set_path(Some("/"))
-        .set_http_only(true)
-        .set_expires(expired_date)
+fn parse_bearer_token(value: &str) -> Option<String> {
+    let (scheme, token) = value.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("bearer") || token.trim().is_empty() {
+        return None;
+    }
+    Some(token.trim().to_string())
 }
 
 pub fn is_logged_in(request: &HttpRequest) -> Result<bool> {
@@ -131,12 +240,21 @@ pub fn is_logged_in(request: &HttpRequest) -> Result<bool> {
     Ok(!security::has_access_token_expired(&user)?)
 }
 
+/// Requests other than GET are only authenticated by the cookie along with a valid CSRF token
 pub fn get_logged_in_user(request: &HttpRequest) -> Result<Option<User>> {
     let bearer = match request.cookies.get(BEARER_COOKIE) {
         Some(val) => val,
         None => return Ok(None),
     };
 
+    if !matches!(request.method, HttpMethod::GET) && !has_valid_csrf_token(request, &bearer.value) {
+        warn!(
+            "rejected request to {}: missing or invalid CSRF token",
+            request.url
+        );
+        return Ok(None);
+    }
+
     let mut user = match UserRepository::get_user_by_bearer(&bearer.value)? {
         Some(user) => user,
         None => return Ok(None),
@@ -157,3 +275,49 @@ pub fn get_logged_in_user(request: &HttpRequest) -> Result<Option<User>> {
 
     Ok(Some(user))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn french() -> Locale {
+        Locale::from_code("fr").unwrap()
+    }
+
+    fn english() -> Locale {
+        Locale::default()
+    }
+
+    #[test]
+    fn test_parse_accept_language_follows_weights() {
+        assert_eq!(
+            vec![french(), english()],
+            parse_accept_language("de-DE, en;q=0.5, fr-CH;q=0.8")
+        );
+        assert_eq!(vec![english(), french()], parse_accept_language("en-GB,fr"));
+    }
+
+    #[test]
+    fn test_parse_accept_language_skips_malformed_entries() {
+        assert_eq!(
+            vec![french()],
+            parse_accept_language("en;q=abc, *;q=0.9, fr;q=0.1, en-US;q=0")
+        );
+        assert!(parse_accept_language("").is_empty());
+        assert!(parse_accept_language(";;,q=1").is_empty());
+    }
+
+    #[test]
+    fn test_bearer_token_of_lower_case_header() {
+        // HTTP/2 clients and some proxies send lower-case header names
+        let headers = [
+            ("content-type", "application/json"),
+            ("authorization", "Bearer abc123"),
+        ];
+        let value = find_header(headers, "Authorization").unwrap();
+        assert_eq!(Some("abc123"), parse_bearer_token(value).as_deref());
+
+        assert_eq!(None, parse_bearer_token("Basic abc123"));
+        assert_eq!(None, parse_bearer_token("Bearer "));
+    }
+}
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+
+/// Lines of an iCalendar file end with CRLF and are folded past 75 octets (RFC 5545)
+const LINE_ENDING: &str = "\r\n";
+const MAX_LINE_OCTETS: usize = 75;
+
+#[derive(Debug)]
+pub struct Event {
+    /// Globally unique, lets calendar apps update the event instead of adding it again
+    pub uid: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub summary: String,
+    pub description: String,
+    pub url: String,
+    /// Whether calendar apps should remind of the event when it starts
+    pub alarm: bool,
+}
+
+/// Calendar published as is, calendar apps subscribe to it and refresh it on their own
+pub fn to_ics(name: &str, events: &[Event], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Advent of Time//Calendar//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+    ];
+
+    for event in events {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", event.uid),
+            format!("DTSTAMP:{}", format_date_time(now)),
+            format!("DTSTART:{}", format_date_time(event.start)),
+            format!("DTEND:{}", format_date_time(event.end)),
+            format!("SUMMARY:{}", escape_text(&event.summary)),
+            format!("DESCRIPTION:{}", escape_text(&event.description)),
+            format!("URL:{}", event.url),
+        ]);
+        if event.alarm {
+            lines.extend([
+                "BEGIN:VALARM".to_string(),
+                "ACTION:DISPLAY".to_string(),
+                format!("DESCRIPTION:{}", escape_text(&event.summary)),
+                "TRIGGER:PT0S".to_string(),
+                "END:VALARM".to_string(),
+            ]);
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold_line(line) + LINE_ENDING)
+        .collect()
+}
+
+fn format_date_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Long lines continue on the next ones, which start with a space. Characters are never split.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_octets = 0;
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str(LINE_ENDING);
+            folded.push(' ');
+            line_octets = 1;
+        }
+        folded.push(c);
+        line_octets += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(alarm: bool) -> Event {
+        let start = Utc.with_ymd_and_hms(2025, 12, 3, 5, 0, 0).unwrap();
+        Event {
+            uid: "day-3-2025@aot.example".to_string(),
+            start,
+            end: start,
+            summary: "Day 3, unlocked".to_string(),
+            description: "Guess the time;\nnow".to_string(),
+            url: "https://aot.example/day/3".to_string(),
+            alarm,
+        }
+    }
+
+    #[test]
+    fn test_to_ics() {
+        let now = Utc.with_ymd_and_hms(2025, 12, 1, 12, 0, 0).unwrap();
+        let ics = to_ics("Advent of Time", &[event(true)], now);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(ics.contains("\r\nDTSTAMP:20251201T120000Z\r\n"));
+        assert!(ics.contains("\r\nDTSTART:20251203T050000Z\r\n"));
+        assert!(ics.contains("\r\nSUMMARY:Day 3\\, unlocked\r\n"));
+        assert!(ics.contains("\r\nDESCRIPTION:Guess the time\\;\\nnow\r\n"));
+        assert!(ics.contains("\r\nTRIGGER:PT0S\r\n"));
+        assert!(!to_ics("AOT", &[event(false)], now).contains("VALARM"));
+    }
+
+    #[test]
+    fn test_fold_line() {
+        assert_eq!("short", fold_line("short"));
+
+        let line = format!("SUMMARY:{}", "é".repeat(40));
+        let folded = fold_line(&line);
+        let lines: Vec<_> = folded.split(LINE_ENDING).collect();
+        assert_eq!(2, lines.len());
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(line, folded.replace("\r\n ", ""));
+    }
+}
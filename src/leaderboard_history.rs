@@ -0,0 +1,179 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use serde::Serialize;
+use std::{thread, time::Duration};
+use utoipa::ToSchema;
+
+use crate::{
+    database::leaderboard_history_repository::LeaderboardHistoryRepository,
+    models::leaderboard_snapshot::{LeaderboardSnapshot, SnapshotEntry},
+    ranking,
+    utils::{self, Day},
+};
+
+const SNAPSHOT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Latest day whose picture has been released at the given time
+pub fn get_latest_released_day(utc_now: DateTime<Utc>) -> Option<Day> {
+    (1..=25)
+        .rev()
+        .find(|&day| utils::is_picture_released(utc_now, day))
+}
+
+/// Ranks of the players who appear on the leaderboard, in leaderboard order
+pub fn compute_current_standings() -> Result<Vec<SnapshotEntry>> {
+    Ok(ranking::get_standings()?
+        .visible
+        .iter()
+        .map(|player| SnapshotEntry {
+            user_id: player.user.id.to_owned(),
+            rank: player.rank,
+            score: player.stats.score,
+        })
+        .collect())
+}
+
+/// Takes a snapshot of the standings if the latest unlocked day does not have one yet
+pub fn take_snapshot_if_due(utc_now: DateTime<Utc>) -> Result<bool> {
+    let day = match get_latest_released_day(utc_now) {
+        Some(day) => day,
+        None => return Ok(false),
+    };
+
+    if LeaderboardHistoryRepository::get_snapshot(day)?.is_some() {
+        return Ok(false);
+    }
+
+    info!("day {day} unlocked, taking leaderboard snapshot");
+    LeaderboardHistoryRepository::create_snapshot(LeaderboardSnapshot {
+        day,
+        taken_at: utc_now,
+        entries: compute_current_standings()?,
+    })?;
+    Ok(true)
+}
+
+/// Periodically checks for newly unlocked days in the background
+pub fn spawn_snapshot_worker() {
+    thread::spawn(|| {
+        loop {
+            if let Err(e) = take_snapshot_if_due(Utc::now()) {
+                error!("failed to take leaderboard snapshot: {e}");
+            }
+            thread::sleep(SNAPSHOT_CHECK_INTERVAL);
+        }
+    });
+}
+
+/// Ranks of a player in every snapshot they appear in, as `(day, rank)` pairs
+pub fn get_rank_history(user_id: &str) -> Result<Vec<(Day, u32)>> {
+    Ok(LeaderboardHistoryRepository::get_all_snapshots()?
+        .iter()
+        .filter_map(|snapshot| snapshot.get_rank(user_id).map(|rank| (snapshot.day, rank)))
+        .collect())
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RankMovement {
+    pub arrow: String,
+    pub class: String,
+}
+
+/// Movement of a player between the last snapshot and their current rank
+pub fn get_rank_movement(previous_rank: Option<u32>, current_rank: u32) -> RankMovement {
+    let (arrow, class) = match previous_rank {
+        None => ("🆕".to_string(), "new"),
+        Some(previous) if previous > current_rank => {
+            (format!("▲{}", previous - current_rank), "up")
+        }
+        Some(previous) if previous < current_rank => {
+            (format!("▼{}", current_rank - previous), "down")
+        }
+        Some(_) => ("=".to_string(), "same"),
+    };
+
+    RankMovement {
+        arrow,
+        class: class.to_string(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RankChartPoint {
+    pub x: u32,
+    pub y: u32,
+    pub day: Day,
+    pub rank: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RankChart {
+    pub width: u32,
+    pub height: u32,
+    /// Points formatted for the `points` attribute of an SVG polyline
+    pub polyline: String,
+    pub points: Vec<RankChartPoint>,
+    pub max_rank: u32,
+}
+
+const CHART_WIDTH: u32 = 500;
+const CHART_HEIGHT: u32 = 200;
+const CHART_PADDING: u32 = 20;
+
+/// Builds a rank-over-time chart from `(day, rank)` pairs, rank 1 being at the top
+pub fn build_rank_chart(ranks: &[(Day, u32)]) -> Option<RankChart> {
+    if ranks.is_empty() {
+        return None;
+    }
+
+    let max_rank = ranks.iter().map(|r| r.1).max().unwrap_or(1).max(2);
+    let inner_width = CHART_WIDTH - 2 * CHART_PADDING;
+    let inner_height = CHART_HEIGHT - 2 * CHART_PADDING;
+    let steps = (ranks.len() as u32 - 1).max(1);
+
+    let points: Vec<_> = ranks
+        .iter()
+        .enumerate()
+        .map(|(i, &(day, rank))| RankChartPoint {
+            x: CHART_PADDING + i as u32 * inner_width / steps,
+            y: CHART_PADDING + (rank - 1) * inner_height / (max_rank - 1),
+            day,
+            rank,
+        })
+        .collect();
+
+    let polyline = points
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(RankChart {
+        width: CHART_WIDTH,
+        height: CHART_HEIGHT,
+        polyline,
+        points,
+        max_rank,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_rank_movement() {
+        assert_eq!("▲2", get_rank_movement(Some(5), 3).arrow);
+        assert_eq!("▼1", get_rank_movement(Some(3), 4).arrow);
+        assert_eq!("=", get_rank_movement(Some(4), 4).arrow);
+        assert_eq!("new", get_rank_movement(None, 4).class);
+    }
+
+    #[test]
+    fn test_build_rank_chart_puts_first_rank_on_top() {
+        let chart = build_rank_chart(&[(1, 3), (2, 1)]).unwrap();
+        assert_eq!("20,180 480,20", chart.polyline);
+        assert!(chart.points[1].y < chart.points[0].y);
+    }
+}
@@ -0,0 +1,101 @@
+use rust_i18n::t;
+use std::iter;
+
+/// Locale used when nothing better matches, every other one falls back to it
+const DEFAULT_LOCALE: &str = "en";
+
+/// A language with a translation file in the `locales` directory, like `fr` or `fr-CA`.
+/// Adding a file there is enough to support a new language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale(&'static str);
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale(DEFAULT_LOCALE)
+    }
+}
+
+impl Locale {
+    /// Locales discovered from the translation files, sorted by code
+    pub fn all() -> Vec<Locale> {
+        rust_i18n::available_locales!()
+            .into_iter()
+            .map(Locale)
+            .collect()
+    }
+
+    pub fn from_code(code: &str) -> Option<Locale> {
+        Self::all()
+            .into_iter()
+            .find(|locale| locale.0.eq_ignore_ascii_case(code))
+    }
+
+    /// Most specific supported locale of a tag, `fr-CA` gives `fr` when there is no `fr-CA` file
+    pub fn from_language_tag(tag: &str) -> Option<Locale> {
+        get_parent_tags(tag).find_map(Self::from_code)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        self.0
+    }
+
+    /// Name of the language in the language itself, as shown by the switcher
+    pub fn native_name(self) -> String {
+        t!("locale.name", locale = self.0).into_owned()
+    }
+
+    /// Codes to look localized files up with, from the most specific one: `fr-CA`, `fr`, `en`
+    pub fn fallback_chain(self) -> Vec<&'static str> {
+        let mut chain: Vec<_> = get_parent_tags(self.0).collect();
+        if !chain.contains(&DEFAULT_LOCALE) {
+            chain.push(DEFAULT_LOCALE);
+        }
+        chain
+    }
+}
+
+/// The tag itself followed by its prefixes, `fr-CA` gives `fr-CA` then `fr`
+fn get_parent_tags(tag: &str) -> impl Iterator<Item = &str> {
+    iter::successors(Some(tag.trim()), |tag| {
+        tag.rsplit_once('-').map(|(parent, _)| parent)
+    })
+    .filter(|tag| !tag.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locales_are_discovered_from_files() {
+        let codes: Vec<_> = Locale::all().into_iter().map(Locale::as_str).collect();
+        for entry in std::fs::read_dir("locales").unwrap() {
+            let path = entry.unwrap().path();
+            let code = path.file_stem().unwrap().to_str().unwrap().to_string();
+            assert!(codes.contains(&code.as_str()), "{code} is not available");
+        }
+        assert!(codes.contains(&DEFAULT_LOCALE));
+    }
+
+    #[test]
+    fn test_from_language_tag() {
+        let french = Locale::from_code("fr").unwrap();
+        assert_eq!(Some(french), Locale::from_code("FR"));
+        assert_eq!(Some(french), Locale::from_language_tag("fr-CA"));
+        assert_eq!(Some(Locale::default()), Locale::from_language_tag("en-US"));
+        assert_eq!(None, Locale::from_language_tag("tlh"));
+        assert_eq!(None, Locale::from_language_tag(""));
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        assert_eq!(vec!["fr-CA", "fr", "en"], Locale("fr-CA").fallback_chain());
+        assert_eq!(vec!["en"], Locale::default().fallback_chain());
+    }
+
+    #[test]
+    fn test_native_name() {
+        assert_eq!("Français", Locale::from_code("fr").unwrap().native_name());
+        assert_eq!("English", Locale::default().native_name());
+    }
+}
@@ -1,21 +1,39 @@
 use crate::database::{
-    picture_meta_repository::PictureMetaRepository, user_repository::UserRepository,
+    api_token_repository::ApiTokenRepository,
+    leaderboard_history_repository::LeaderboardHistoryRepository,
+    league_repository::LeagueRepository, picture_meta_repository::PictureMetaRepository,
+    score_cache_repository::ScoreCacheRepository, team_repository::TeamRepository,
+    user_repository::UserRepository,
 };
 use config::Config;
 use log::{LevelFilter, info, warn};
-use rtfw_http::{file_server::FileServer, http::HttpMethod, router::Router, web_server::WebServer};
+use rtfw_http::{http::HttpMethod, router::Router, web_server::WebServer};
 
+mod accuracy;
+mod achievements;
 mod config;
 mod controllers;
 mod database;
+mod embedded;
 mod http_helpers;
+mod icalendar;
+mod leaderboard_history;
+mod locale;
+mod markdown;
 mod models;
 mod oauth2;
+mod openapi;
+mod ranking;
+mod rate_limit;
 mod routes;
+mod score_cache;
+mod scoring;
 mod security;
+mod security_headers;
+mod templates;
 mod utils;
 
-rust_i18n::i18n!("locales");
+rust_i18n::i18n!("locales", fallback = "en");
 
 fn main() -> anyhow::Result<()> {
     env_logger::Builder::new()
@@ -26,29 +44,41 @@ fn main() -> anyhow::Result<()> {
     let config = Config::get()?;
 
     UserRepository::initialize_database()?;
+    LeagueRepository::initialize_database()?;
+    TeamRepository::initialize_database()?;
     PictureMetaRepository::initialize_database()?;
+    LeaderboardHistoryRepository::initialize_database()?;
+    ScoreCacheRepository::initialize_database()?;
+    ApiTokenRepository::initialize_database()?;
 
-    let file_server = FileServer::new()
-        .map_file("/favicon.ico", "src/assets/favicon.ico")?
-        .map_file("/main.css", "src/styles/main.css")?
-        .map_file("/home.css", "src/styles/home.css")?
-        .map_file("/about.css", "src/styles/about.css")?
-        .map_file("/day.css", "src/styles/day.css")?
-        .map_file("/leaderboard.css", "src/styles/leaderboard.css")?
-        .map_file("/profile.css", "src/styles/profile.css")?
-        .map_dir("/static", "src/assets/")?
-        .map_dir("/scripts", "src/scripts/")?;
+    // cached points are recomputed when the pictures or the scoring changed, or when asked to
+    if std::env::args().any(|arg| arg == "--recompute-scores") {
+        score_cache::invalidate()?;
+    }
+    score_cache::ensure_scores_up_to_date()?;
+    achievements::backfill_achievements()?;
+    templates::init()?;
+
+    // assets are embedded in the binary, each one is served under its plain and hashed URLs
+    let mut router = Router::new();
+    for url in controllers::assets::get_asset_urls() {
+        router = router.get(url, controllers::assets::get_asset)?;
+    }
 
-    let router = Router::new()
+    router = router
         // index
         .get("/", routes::get_index)?
         .get("/home", routes::get_index)?
         .get("/index", routes::get_index)?
         // about
         .get("/about", routes::get_about)?
+        // language switcher
+        .post("/locale", routes::post_locale)?
+        // scoring
+        .get("/scoring", controllers::scoring::get_scoring)?
         // auth
         .get("/auth/login", controllers::auth::get_login)?
-        .get("/auth/logout", controllers::auth::get_logout)?
+        .post("/auth/logout", controllers::auth::post_logout)?
         .get("/auth/oauth2", controllers::auth::get_oauth2_login)?
         .get(
             "/auth/oauth2-redirect/discord",
@@ -63,15 +93,56 @@ fn main() -> anyhow::Result<()> {
             controllers::auth::get_github_oauth2_redirect,
         )?
         .get("/auth/me", controllers::profile::get_me)?
+        .post("/auth/me/tokens", controllers::api_token::post_create_token)?
+        .post(
+            "/auth/me/tokens/:id/revoke",
+            controllers::api_token::post_revoke_token,
+        )?
         .get("/leaderboard", controllers::leaderboard::get_leaderboard)?
+        // leagues
+        .get("/leagues", controllers::league::get_leagues)?
+        .get("/leagues/:id", controllers::league::get_league)?
+        .post("/leagues", controllers::league::post_create_league)?
+        .post("/leagues/join", controllers::league::post_join_league)?
+        .post(
+            "/leagues/:id/remove",
+            controllers::league::post_remove_member,
+        )?
+        .post("/leagues/:id/leave", controllers::league::post_leave_league)?
+        // teams
+        .get("/teams", controllers::team::get_teams)?
+        .get("/teams/:id", controllers::team::get_team)?
+        .post("/teams", controllers::team::post_create_team)?
+        .post("/teams/:id/members", controllers::team::post_assign_member)?
+        .post("/teams/:id/join", controllers::team::post_join_team)?
         // day
         .get("/day/:id", controllers::day::get_single_day)?
+        .get("/day/:id/results", controllers::day::get_day_results)?
         .get("/day-pic/:id", controllers::day::get_day_picture)?
+        // feeds
+        .get("/feed.xml", controllers::feed::get_feed)?
+        .get("/calendar.ics", controllers::calendar::get_calendar_ics)?
         // guess
         .post("/guess/:id", controllers::guess::post_guess)?
-        // others
-        .catch_all(HttpMethod::GET, routes::catcher_get_404)?
-        .set_file_server(file_server);
+        // api
+        .get("/api/openapi.json", controllers::api::get_openapi)?
+        .get("/api/v1/calendar", controllers::api::get_calendar)?
+        .get("/api/v1/days/:id", controllers::api::get_day)?
+        .get("/api/v1/days/:id/stats", controllers::api::get_day_stats)?
+        .post("/api/v1/days/:id/guess", controllers::api::post_day_guess)?
+        .get("/api/v1/me", controllers::api::get_me)?
+        .get("/api/v1/leaderboard", controllers::api::get_leaderboard)?;
+
+    // others, unmatched API requests get a JSON error whatever their method
+    for method in [
+        HttpMethod::GET,
+        HttpMethod::POST,
+        HttpMethod::PUT,
+        HttpMethod::PATCH,
+        HttpMethod::DELETE,
+    ] {
+        router = router.catch_all(method, routes::catcher_404)?;
+    }
 
     info!("ROUTER: {:#?}", router);
     info!("server listening on: {}", config.hostname);
@@ -86,6 +157,8 @@ DEV MODE is enabled, make sure to turn it off on Prod!
         warn!("{msg}");
     }
 
+    leaderboard_history::spawn_snapshot_worker();
+
     let server = WebServer::new(&config.hostname, router)?;
     server.run()
 }
@@ -0,0 +1,488 @@
+//! A small markdown renderer, covering the subset of CommonMark used by the webapp:
+//! ATX headings, paragraphs, hard line breaks, lists, block quotes, fenced code blocks, code spans,
+//! emphasis, links and backslash escapes. Two extensions are supported: `~~strikethrough~~` and
+//! `{#id}` at the end of a heading to set its ID.
+//!
+//! Raw HTML is never passed through: every character of the source is escaped and the only tags
+//! in the output are the ones written by the renderer. Links are only kept for allowed URLs.
+
+/// Schemes allowed in links, on top of relative URLs
+const ALLOWED_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Whether a link target is safe to put in an `href`: relative URLs, or one of the allowed schemes
+pub fn is_url_allowed(url: &str) -> bool {
+    // browsers ignore whitespace and control characters in schemes, e.g. `java\tscript:`
+    if url.is_empty() || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return false;
+    }
+
+    if url.starts_with("//") {
+        return false;
+    }
+
+    match url.find(':') {
+        Some(colon) if !url[..colon].contains(['/', '?', '#']) => {
+            let scheme = url[..colon].to_ascii_lowercase();
+            ALLOWED_SCHEMES.contains(&scheme.as_str())
+        }
+        _ => true,
+    }
+}
+
+/// Renders a whole document
+pub fn to_html(markdown: &str) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    render_blocks(&lines)
+}
+
+/// Renders a single line of text, without wrapping it in a paragraph
+pub fn inline_to_html(markdown: &str) -> String {
+    render_inline(&markdown.chars().collect::<Vec<_>>())
+}
+
+fn render_blocks(lines: &[&str]) -> String {
+    let mut html = String::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+
+        if trimmed.is_empty() {
+            i += 1;
+        } else if let Some((level, text)) = parse_heading(trimmed) {
+            html.push_str(&render_heading(level, text));
+            i += 1;
+        } else if trimmed.starts_with("```") {
+            let end = lines[i + 1..]
+                .iter()
+                .position(|l| l.trim_start().starts_with("```"))
+                .map_or(lines.len(), |pos| i + 1 + pos);
+            let code = lines[i + 1..end].join("\n");
+            html.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&code)));
+            i = end + 1;
+        } else if trimmed.starts_with('>') {
+            let end = find_block_end(lines, i, |l| l.trim_start().starts_with('>'));
+            let quoted: Vec<&str> = lines[i..end]
+                .iter()
+                .map(|l| {
+                    let l = &l.trim_start()[1..];
+                    l.strip_prefix(' ').unwrap_or(l)
+                })
+                .collect();
+            html.push_str(&format!(
+                "<blockquote>\n{}</blockquote>\n",
+                render_blocks(&quoted)
+            ));
+            i = end;
+        } else if let Some(ordered) = parse_list_item(trimmed).map(|(ordered, _)| ordered) {
+            let end = find_block_end(lines, i, |l| {
+                let t = l.trim_start();
+                parse_list_item(t).is_some_and(|(o, _)| o == ordered)
+                    || (l.starts_with([' ', '\t']) && !t.is_empty())
+            });
+            html.push_str(&render_list(&lines[i..end], ordered));
+            i = end;
+        } else {
+            let end = find_block_end(lines, i, |l| {
+                let t = l.trim_start();
+                !t.is_empty()
+                    && parse_heading(t).is_none()
+                    && !t.starts_with("```")
+                    && !t.starts_with('>')
+                    && parse_list_item(t).is_none()
+            });
+            let text = lines[i..end].join("\n");
+            html.push_str(&format!("<p>{}</p>\n", inline_to_html(text.trim())));
+            i = end;
+        }
+    }
+    html
+}
+
+/// Index of the first line after `start` that does not belong to the block
+fn find_block_end(lines: &[&str], start: usize, belongs: impl Fn(&str) -> bool) -> usize {
+    lines[start + 1..]
+        .iter()
+        .position(|l| !belongs(l))
+        .map_or(lines.len(), |pos| start + 1 + pos)
+}
+
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+
+    let rest = &line[level..];
+    if rest.is_empty() {
+        Some((level, ""))
+    } else if rest.starts_with([' ', '\t']) {
+        Some((level, rest.trim()))
+    } else {
+        None
+    }
+}
+
+fn render_heading(level: usize, text: &str) -> String {
+    let (text, id) = match text.strip_suffix('}').and_then(|t| t.rsplit_once("{#")) {
+        Some((text, id))
+            if !id.is_empty()
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+        {
+            (text.trim_end(), Some(id))
+        }
+        _ => (text, None),
+    };
+
+    let id_attr = id.map(|id| format!(" id=\"{id}\"")).unwrap_or_default();
+    format!("<h{level}{id_attr}>{}</h{level}>\n", inline_to_html(text))
+}
+
+/// Whether the line starts a list item, and whether the list is ordered
+fn parse_list_item(line: &str) -> Option<(bool, &str)> {
+    if let Some(text) = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))
+    {
+        return Some((false, text));
+    }
+
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if (1..=9).contains(&digits) {
+        let rest = &line[digits..];
+        if let Some(text) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return Some((true, text));
+        }
+    }
+    None
+}
+
+fn render_list(lines: &[&str], ordered: bool) -> String {
+    let mut items: Vec<String> = vec![];
+    for line in lines {
+        match parse_list_item(line.trim_start()) {
+            Some((_, text)) => items.push(text.trim().to_string()),
+            // continuation of the previous item
+            None => {
+                if let Some(item) = items.last_mut() {
+                    item.push('\n');
+                    item.push_str(line.trim());
+                }
+            }
+        }
+    }
+
+    let tag = if ordered { "ol" } else { "ul" };
+    let items: String = items
+        .iter()
+        .map(|item| format!("<li>{}</li>\n", inline_to_html(item)))
+        .collect();
+    format!("<{tag}>\n{items}</{tag}>\n")
+}
+
+fn render_inline(chars: &[char]) -> String {
+    let mut html = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if chars.get(i + 1) == Some(&'\n') => {
+                html.push_str("<br>\n");
+                i += 2;
+            }
+            '\\' if chars.get(i + 1).is_some_and(|n| n.is_ascii_punctuation()) => {
+                html.push_str(&escape_html(&chars[i + 1].to_string()));
+                i += 2;
+            }
+            '\n' => {
+                // two trailing spaces make a hard line break
+                if html.ends_with("  ") {
+                    html.truncate(html.trim_end_matches(' ').len());
+                    html.push_str("<br>");
+                }
+                html.push('\n');
+                i += 1;
+            }
+            '`' => match parse_code_span(chars, i) {
+                Some((code, end)) => {
+                    html.push_str(&format!("<code>{}</code>", escape_html(&code)));
+                    i = end;
+                }
+                None => {
+                    let run = count_run(chars, i, '`');
+                    html.push_str(&"`".repeat(run));
+                    i += run;
+                }
+            },
+            '[' => match parse_link(chars, i) {
+                Some((label, url, end)) => {
+                    if is_url_allowed(&url) {
+                        html.push_str(&format!(
+                            "<a href=\"{}\">{}</a>",
+                            escape_html(&url),
+                            render_inline(&label)
+                        ));
+                    } else {
+                        html.push_str(&render_inline(&label));
+                    }
+                    i = end;
+                }
+                None => {
+                    html.push('[');
+                    i += 1;
+                }
+            },
+            '*' | '_' | '~' => match parse_emphasis(chars, i) {
+                Some((tag, inner, end)) => {
+                    html.push_str(&format!("<{tag}>{}</{tag}>", render_inline(&inner)));
+                    i = end;
+                }
+                None => {
+                    let run = count_run(chars, i, c);
+                    html.push_str(&c.to_string().repeat(run));
+                    i += run;
+                }
+            },
+            _ => {
+                html.push_str(&escape_html(&c.to_string()));
+                i += 1;
+            }
+        }
+    }
+    html
+}
+
+fn count_run(chars: &[char], start: usize, c: char) -> usize {
+    chars[start..].iter().take_while(|&&x| x == c).count()
+}
+
+/// A code span closes with a run of backticks of the same length
+fn parse_code_span(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let run = count_run(chars, start, '`');
+    let mut i = start + run;
+    while i < chars.len() {
+        if chars[i] == '`' {
+            let closing = count_run(chars, i, '`');
+            if closing == run {
+                let code: String = chars[start + run..i].iter().collect();
+                let code = match code.strip_prefix(' ').and_then(|c| c.strip_suffix(' ')) {
+                    Some(stripped) if !stripped.trim().is_empty() => stripped.to_string(),
+                    _ => code,
+                };
+                return Some((code.replace('\n', " "), i + closing));
+            }
+            i += closing;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+/// `[label](url)`, returns the label, the URL and the index after the link
+fn parse_link(chars: &[char], start: usize) -> Option<(Vec<char>, String, usize)> {
+    let mut depth = 0;
+    let mut i = start;
+    let label_end = loop {
+        match chars.get(i)? {
+            '\\' => i += 1,
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    break i;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    };
+
+    if chars.get(label_end + 1) != Some(&'(') {
+        return None;
+    }
+
+    let url_start = label_end + 2;
+    let url_len = chars[url_start..].iter().position(|&c| c == ')')?;
+    let url: String = chars[url_start..url_start + url_len].iter().collect();
+    Some((
+        chars[start + 1..label_end].to_vec(),
+        url.trim().to_string(),
+        url_start + url_len + 1,
+    ))
+}
+
+/// `*em*`, `_em_`, `**strong**`, `__strong__` and `~~del~~`, returns the tag, the inner text and
+/// the index after the closing delimiter
+fn parse_emphasis(chars: &[char], start: usize) -> Option<(&'static str, Vec<char>, usize)> {
+    let delimiter = chars[start];
+    let run = count_run(chars, start, delimiter);
+    let (tag, len) = match (delimiter, run) {
+        ('~', 2..) => ("del", 2),
+        ('~', _) => return None,
+        (_, 2..) => ("strong", 2),
+        _ => ("em", 1),
+    };
+
+    // the opening delimiter must be followed by text, and `_` cannot open within a word
+    let after = *chars.get(start + len)?;
+    if after.is_whitespace() {
+        return None;
+    }
+    if delimiter == '_' && start > 0 && chars[start - 1].is_alphanumeric() {
+        return None;
+    }
+
+    let mut i = start + len;
+    while i < chars.len() {
+        if chars[i] == '\\' {
+            i += 2;
+            continue;
+        }
+
+        if chars[i] == '`'
+            && let Some((_, end)) = parse_code_span(chars, i)
+        {
+            i = end;
+            continue;
+        }
+
+        if chars[i] != delimiter {
+            i += 1;
+            continue;
+        }
+
+        let closing_run = count_run(chars, i, delimiter);
+        let close_at = i + closing_run - len;
+        let before = chars[close_at - 1];
+        let next = chars.get(close_at + len);
+        let can_close = closing_run >= len
+            && close_at > start + len
+            && !before.is_whitespace()
+            && !(delimiter == '_' && next.is_some_and(|n| n.is_alphanumeric()));
+        if can_close {
+            return Some((tag, chars[start + len..close_at].to_vec(), close_at + len));
+        }
+        i += closing_run;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_formatting() {
+        assert_eq!(
+            inline_to_html("**bold**, *italic*, _italic_ and `code`"),
+            "<strong>bold</strong>, <em>italic</em>, <em>italic</em> and <code>code</code>"
+        );
+        assert_eq!(
+            inline_to_html("Alacri*tty*, ki*tty*"),
+            "Alacri<em>tty</em>, ki<em>tty</em>"
+        );
+        assert_eq!(inline_to_html("snake_case_name"), "snake_case_name");
+        assert_eq!(
+            inline_to_html("***both***"),
+            "<strong><em>both</em></strong>"
+        );
+        assert_eq!(inline_to_html("~~gone~~"), "<del>gone</del>");
+        assert_eq!(inline_to_html("2 * 3 * 4"), "2 * 3 * 4");
+        assert_eq!(inline_to_html(r"\*not italic\*"), "*not italic*");
+        assert_eq!(
+            inline_to_html("`*not italic*`"),
+            "<code>*not italic*</code>"
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        assert_eq!(
+            inline_to_html("[C++](https://en.wikipedia.org/wiki/C%2B%2B) in 1985"),
+            "<a href=\"https://en.wikipedia.org/wiki/C%2B%2B\">C++</a> in 1985"
+        );
+        assert_eq!(
+            inline_to_html("[**bold** link](/scoring)"),
+            "<a href=\"/scoring\"><strong>bold</strong> link</a>"
+        );
+        assert_eq!(
+            inline_to_html("[anchor](#faq-point-system)"),
+            "<a href=\"#faq-point-system\">anchor</a>"
+        );
+        assert_eq!(inline_to_html("[not a link]"), "[not a link]");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let markdown = "## Title {#the-title}\n\nFirst line\\\nsecond line\n\n- one\n- *two*\n\n> quoted\n\n```\n<b>code</b>\n```";
+        assert_eq!(
+            to_html(markdown),
+            "<h2 id=\"the-title\">Title</h2>\n\
+             <p>First line<br>\nsecond line</p>\n\
+             <ul>\n<li>one</li>\n<li><em>two</em></li>\n</ul>\n\
+             <blockquote>\n<p>quoted</p>\n</blockquote>\n\
+             <pre><code>&lt;b&gt;code&lt;/b&gt;</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_html_is_escaped() {
+        assert_eq!(
+            inline_to_html("<script>alert('xss')</script>"),
+            "&lt;script&gt;alert(&#39;xss&#39;)&lt;/script&gt;"
+        );
+        assert_eq!(
+            inline_to_html("<button onclick=\"alert('hi')\">CLICK</button>"),
+            "&lt;button onclick=&quot;alert(&#39;hi&#39;)&quot;&gt;CLICK&lt;/button&gt;"
+        );
+        assert_eq!(
+            inline_to_html("**<img src=x onerror=alert(1)>**"),
+            "<strong>&lt;img src=x onerror=alert(1)&gt;</strong>"
+        );
+        assert_eq!(
+            to_html("# <i>title</i> {#x\"onmouseover=\"alert(1)}"),
+            "<h1>&lt;i&gt;title&lt;/i&gt; {#x&quot;onmouseover=&quot;alert(1)}</h1>\n"
+        );
+    }
+
+    #[test]
+    fn test_unsafe_urls_are_dropped() {
+        for url in [
+            "javascript:alert(1)",
+            "JavaScript:alert(1)",
+            "java\tscript:alert(1)",
+            " javascript:alert(1)",
+            "data:text/html,<script>alert(1)</script>",
+            "vbscript:msgbox(1)",
+            "//evil.example",
+        ] {
+            let html = inline_to_html(&format!("[click]({url})"));
+            assert!(!html.contains("<a"), "{url:?} rendered as {html:?}");
+        }
+
+        // quotes cannot break out of the attribute
+        assert_eq!(
+            inline_to_html("[x](/a\"onclick=\"alert(1))"),
+            "<a href=\"/a&quot;onclick=&quot;alert(1\">x</a>)"
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Achievement {
+    /// Guessed the exact minute a picture was taken
+    PerfectMinute,
+    /// Guessed five days in a row
+    Streak5,
+    /// Guessed within 10 minutes of a picture being released
+    EarlyBird,
+    /// Guessed every single day of the calendar
+    FullCalendar,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 4] = [
+        Achievement::PerfectMinute,
+        Achievement::Streak5,
+        Achievement::EarlyBird,
+        Achievement::FullCalendar,
+    ];
+
+    /// Identifier used in the database and as the i18n key
+    pub fn key(&self) -> &'static str {
+        match self {
+            Achievement::PerfectMinute => "perfect_minute",
+            Achievement::Streak5 => "streak_5",
+            Achievement::EarlyBird => "early_bird",
+            Achievement::FullCalendar => "full_calendar",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Achievement::PerfectMinute => "🎯",
+            Achievement::Streak5 => "🔥",
+            Achievement::EarlyBird => "🐦",
+            Achievement::FullCalendar => "📅",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UnlockedAchievement {
+    pub achievement: Achievement,
+    /// The time at which the achievement was unlocked by that user
+    pub unlocked_at: DateTime<Utc>,
+}
@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenScope {
+    /// Can only read data: calendar, days, profile, leaderboard...
+    ReadOnly,
+    /// Can read data and submit guesses on behalf of the user
+    SubmitGuess,
+}
+
+impl TokenScope {
+    pub fn allows(&self, required: TokenScope) -> bool {
+        match required {
+            TokenScope::ReadOnly => true,
+            TokenScope::SubmitGuess => *self == TokenScope::SubmitGuess,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ApiToken {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    /// SHA-256 of the token, the token itself is only shown once to the user
+    pub token_hash: String,
+    pub scope: TokenScope,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
@@ -54,6 +54,9 @@ impl OAuthUserInfoHandler<DiscordUserResponse> for DiscordUserInfoHandler {
             refresh_token: None,
             oauth_provider: "discord".to_string(),
             hidden: false,
+            achievements: vec![],
+            team_id: None,
+            preferred_locale: None,
         };
         user.set_auth(oauth2_response)?;
 
@@ -91,6 +91,9 @@ impl OAuthUserInfoHandler<GitHubUserResponse> for GitHubUserInfoHandler {
             refresh_token: None,
             oauth_provider: "github".to_string(),
             hidden: false,
+            achievements: vec![],
+            team_id: None,
+            preferred_locale: None,
         };
         user.set_auth(oauth2_response)?;
 
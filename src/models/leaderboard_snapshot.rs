@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::Day;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LeaderboardSnapshot {
+    /// The day whose unlock triggered the snapshot
+    pub day: Day,
+    pub taken_at: DateTime<Utc>,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SnapshotEntry {
+    pub user_id: String,
+    pub rank: u32,
+    pub score: u32,
+}
+
+impl LeaderboardSnapshot {
+    pub fn get_rank(&self, user_id: &str) -> Option<u32> {
+        self.entries
+            .iter()
+            .find(|e| e.user_id == user_id)
+            .map(|e| e.rank)
+    }
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct League {
+    pub id: String,
+    pub name: String,
+    /// Code shared by the members to let other players join the league
+    pub invite_code: String,
+    pub owner_id: String,
+    /// Members allowed to remove other members, the owner is always one of them
+    pub admin_ids: Vec<String>,
+    pub member_ids: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl League {
+    pub fn new(id: String, name: String, invite_code: String, owner_id: &str) -> League {
+        League {
+            id,
+            name,
+            invite_code,
+            owner_id: owner_id.to_string(),
+            admin_ids: vec![owner_id.to_string()],
+            member_ids: vec![owner_id.to_string()],
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn is_member(&self, user_id: &str) -> bool {
+        self.member_ids.iter().any(|id| id == user_id)
+    }
+
+    pub fn is_admin(&self, user_id: &str) -> bool {
+        self.admin_ids.iter().any(|id| id == user_id)
+    }
+
+    pub fn remove_member(&mut self, user_id: &str) {
+        self.member_ids.retain(|id| id != user_id);
+        self.admin_ids.retain(|id| id != user_id);
+    }
+}
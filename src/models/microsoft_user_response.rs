@@ -52,6 +52,9 @@ impl OAuthUserInfoHandler<MicrosoftUserResponse> for MicrosoftUserInfoHandler {
             refresh_token: None,
             oauth_provider: "microsoft".to_string(),
             hidden: false,
+            achievements: vec![],
+            team_id: None,
+            preferred_locale: None,
         };
         user.set_auth(oauth2_response)?;
 
@@ -1,7 +1,12 @@
+pub mod achievement;
+pub mod api_token;
 pub mod discord_user_response;
 pub mod github_user_response;
+pub mod leaderboard_snapshot;
+pub mod league;
 pub mod microsoft_user_response;
 pub mod oauth2_response;
 pub mod oauth_user_info_handler;
 pub mod picture;
+pub mod team;
 pub mod user;
@@ -40,3 +40,17 @@ impl Picture {
             .parse::<u32>()?)
     }
 }
+
+#[cfg(test)]
+impl Picture {
+    /// Picture of the given day taken at `time_taken`, like `12:34`
+    pub fn for_test(day: Day, time_taken: &str) -> Picture {
+        Picture {
+            id: day,
+            path: PathBuf::from(format!("day-pics/{day}.jpg")),
+            original_date: String::new(),
+            time_taken: time_taken.to_string(),
+            location: None,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::TeamAggregation;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Team {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Team {
+    pub fn new(id: String, name: String) -> Team {
+        Team {
+            id,
+            name,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Combines the total scores of the members of a team into a single team score
+pub fn aggregate_scores(mut scores: Vec<u32>, aggregation: TeamAggregation) -> u32 {
+    match aggregation {
+        TeamAggregation::Sum => scores.iter().sum(),
+        TeamAggregation::Average if scores.is_empty() => 0,
+        TeamAggregation::Average => scores.iter().sum::<u32>() / scores.len() as u32,
+        TeamAggregation::BestN { n } => {
+            scores.sort_unstable_by(|a, b| b.cmp(a));
+            scores.iter().take(n).sum()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_scores() {
+        let scores = vec![100, 400, 250, 50];
+        assert_eq!(800, aggregate_scores(scores.clone(), TeamAggregation::Sum));
+        assert_eq!(
+            200,
+            aggregate_scores(scores.clone(), TeamAggregation::Average)
+        );
+        assert_eq!(
+            650,
+            aggregate_scores(scores, TeamAggregation::BestN { n: 2 })
+        );
+    }
+
+    #[test]
+    fn test_aggregate_scores_empty_team() {
+        assert_eq!(0, aggregate_scores(vec![], TeamAggregation::Average));
+        assert_eq!(0, aggregate_scores(vec![], TeamAggregation::BestN { n: 3 }));
+    }
+}
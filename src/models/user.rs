@@ -4,8 +4,12 @@ use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, time::Duration};
 
 use crate::{
+    config::Config,
     database::picture_meta_repository::PictureMetaRepository,
-    models::oauth2_response::OAuth2Response,
+    models::{
+        achievement::{Achievement, UnlockedAchievement},
+        oauth2_response::OAuth2Response,
+    },
     utils::{self, Day},
 };
 
@@ -20,6 +24,13 @@ pub struct User {
     pub refresh_token: Option<String>,
     pub oauth_provider: String,
     pub hidden: bool,
+    #[serde(default)]
+    pub achievements: Vec<UnlockedAchievement>,
+    #[serde(default)]
+    pub team_id: Option<String>,
+    /// Code of the language picked with the switcher, like `fr`
+    #[serde(default)]
+    pub preferred_locale: Option<String>,
 }
 
 impl User {
@@ -27,6 +38,23 @@ impl User {
         self.guess_data.contains_key(&day)
     }
 
+    pub fn has_achievement(&self, achievement: Achievement) -> bool {
+        self.achievements
+            .iter()
+            .any(|unlocked| unlocked.achievement == achievement)
+    }
+
+    pub fn has_used_joker(&self) -> bool {
+        self.get_joker_day().is_some()
+    }
+
+    pub fn get_joker_day(&self) -> Option<Day> {
+        self.guess_data
+            .iter()
+            .find(|(_, data)| data.joker)
+            .map(|(&day, _)| day)
+    }
+
     pub fn get_total_score(&self) -> Result<u32> {
         self.guess_data
             .keys()
@@ -36,11 +64,22 @@ impl User {
     pub fn get_points(&self, day: Day) -> Result<u32> {
         match self.guess_data.get(&day) {
             Some(data) => {
-                // info!("received guess for day {day}: {guess:?}");
-                let picture = PictureMetaRepository::get_picture(day)?
-                    .context("picture should exist for guessed day")?;
-                ensure!(picture.day() == day);
-                utils::compute_score(&picture, data.hm)
+                let points = match data.points {
+                    Some(points) => points,
+                    None => {
+                        // not cached yet, happens for guesses made before the cache existed
+                        let picture = PictureMetaRepository::get_picture(day)?
+                            .context("picture should exist for guessed day")?;
+                        ensure!(picture.day() == day);
+                        utils::compute_score(&picture, data.hm)?
+                    }
+                };
+                let multiplier = if data.joker {
+                    Config::get()?.joker.multiplier
+                } else {
+                    1
+                };
+                Ok(points * multiplier)
             }
 
             None => Ok(0),
@@ -76,6 +115,12 @@ pub struct GuessData {
     pub taken_at: DateTime<Utc>,
     /// The hours/minutes keypair submitted by the user
     pub hm: (u32, u32),
+    /// Whether the user played their joker on that day
+    #[serde(default)]
+    pub joker: bool,
+    /// Points awarded for the guess before the joker multiplier, cached at guess time
+    #[serde(default)]
+    pub points: Option<u32>,
 }
 
 impl GuessData {
@@ -83,6 +128,8 @@ impl GuessData {
         GuessData {
             taken_at,
             hm: guess_hm,
+            joker: false,
+            points: None,
         }
     }
 
@@ -90,3 +137,24 @@ impl GuessData {
         format!("{:02}:{:02}", self.hm.0, self.hm.1)
     }
 }
+
+#[cfg(test)]
+impl User {
+    /// Player without any guess, the username doubles as the ID
+    pub fn for_test(username: &str) -> User {
+        User {
+            id: username.to_string(),
+            username: username.to_string(),
+            oauth_username: username.to_string(),
+            guess_data: HashMap::new(),
+            access_token: String::new(),
+            access_token_expire_at: None,
+            refresh_token: None,
+            oauth_provider: "github".to_string(),
+            hidden: false,
+            achievements: vec![],
+            team_id: None,
+            preferred_locale: None,
+        }
+    }
+}
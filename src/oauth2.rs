@@ -1,9 +1,9 @@
 use anyhow::Result;
 use log::debug;
-use rtfw_http::http::{HttpResponse, HttpResponseBuilder, response_status_codes::HttpStatusCode};
+use rtfw_http::http::{HttpResponse, response_status_codes::HttpStatusCode};
 use std::collections::HashMap;
 
-use crate::{config::OAuth2Config, models::oauth2_response::OAuth2Response};
+use crate::{config::OAuth2Config, http_helpers, models::oauth2_response::OAuth2Response};
 
 pub fn redirect_to_authorize(config: &OAuth2Config) -> Result<HttpResponse> {
     let authorize_url = config.authorize_url.to_owned();
@@ -18,7 +18,7 @@ pub fn redirect_to_authorize(config: &OAuth2Config) -> Result<HttpResponse> {
     );
 
     debug!("{authorize_request}");
-    HttpResponseBuilder::new()
+    http_helpers::response()
         .set_status(HttpStatusCode::Found)
         .set_header("Location", &authorize_request)
         .build()
@@ -0,0 +1,126 @@
+use utoipa::{
+    Modify, OpenApi,
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+};
+
+use crate::{controllers::api, http_helpers::BEARER_COOKIE};
+
+/// OpenAPI description of the JSON API, served at `/api/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Advent Of Time API",
+        description = "Versioned JSON API of the Advent Of Time, see the README for an overview"
+    ),
+    paths(
+        api::get_openapi,
+        api::get_calendar,
+        api::get_day,
+        api::get_day_stats,
+        api::post_day_guess,
+        api::get_me,
+        api::get_leaderboard,
+    ),
+    modifiers(&SecuritySchemes),
+    tags(
+        (name = "days", description = "Calendar, pictures and guesses"),
+        (name = "players", description = "Profiles and leaderboard"),
+        (name = "meta", description = "Description of the API itself"),
+    )
+)]
+pub struct ApiDoc;
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_token",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some(
+                        "Personal API token created from the profile page, \
+                         either `read_only` or `submit_guess`",
+                    ))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "session",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(BEARER_COOKIE))),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+    use std::collections::BTreeSet;
+
+    /// `(method, path)` of every route registered under `/api` in `main.rs`,
+    /// with `:param` segments rewritten to the OpenAPI `{param}` syntax
+    fn get_registered_api_routes() -> BTreeSet<(String, String)> {
+        let main_rs = include_str!("main.rs");
+        let route_re = Regex::new(r#"\.(get|post|put|patch|delete)\(\s*"(/api/[^"]*)""#).unwrap();
+        let param_re = Regex::new(r":(\w+)").unwrap();
+        route_re
+            .captures_iter(main_rs)
+            .map(|c| {
+                (
+                    c[1].to_string(),
+                    param_re.replace_all(&c[2], "{$1}").to_string(),
+                )
+            })
+            .collect()
+    }
+
+    fn get_documented_routes() -> BTreeSet<(String, String)> {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        spec["paths"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .flat_map(|(path, item)| {
+                item.as_object()
+                    .unwrap()
+                    .keys()
+                    .map(move |method| (method.to_owned(), path.to_owned()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_every_api_route_is_documented() {
+        let registered = get_registered_api_routes();
+        assert!(!registered.is_empty(), "no api route found in main.rs");
+
+        let documented = get_documented_routes();
+        let missing: Vec<_> = registered.difference(&documented).collect();
+        assert!(
+            missing.is_empty(),
+            "routes missing from the spec: {missing:?}"
+        );
+        let unknown: Vec<_> = documented.difference(&registered).collect();
+        assert!(
+            unknown.is_empty(),
+            "documented routes not registered: {unknown:?}"
+        );
+    }
+
+    #[test]
+    fn test_schemas_are_referenced() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let schemas = spec["components"]["schemas"].as_object().unwrap();
+        for name in [
+            "SubmitGuessRequest",
+            "DayDto",
+            "LeaderboardUserEntry",
+            "ApiErrorResponse",
+        ] {
+            assert!(schemas.contains_key(name), "missing schema: {name}");
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::{
+    cmp::Ordering,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    config::{Config, Tiebreaker},
+    database::{picture_meta_repository::PictureMetaRepository, user_repository::UserRepository},
+    models::{picture::Picture, user::User},
+    utils,
+};
+
+/// Everything players can be compared on when ranking them
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerStats {
+    pub score: u32,
+    pub perfect_guesses: u32,
+    pub total_error_minutes: u32,
+    /// Time of the latest guess, i.e. when the player reached their current score
+    pub last_submission: Option<DateTime<Utc>>,
+}
+
+impl PlayerStats {
+    pub fn compute(user: &User, pictures: &[Picture]) -> Result<PlayerStats> {
+        let mut perfect_guesses = 0;
+        let mut total_error_minutes = 0;
+        for (day, guess) in user.guess_data.iter() {
+            if let Some(picture) = pictures.iter().find(|p| p.day() == *day) {
+                let diff = utils::time_diff_minutes(picture, guess.hm)?;
+                total_error_minutes += diff;
+                if diff == 0 {
+                    perfect_guesses += 1;
+                }
+            }
+        }
+
+        Ok(PlayerStats {
+            score: user.get_total_score()?,
+            perfect_guesses,
+            total_error_minutes,
+            last_submission: user.guess_data.values().map(|g| g.taken_at).max(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RankedPlayer {
+    pub rank: u32,
+    pub user: User,
+    pub stats: PlayerStats,
+}
+
+/// Orders two players, `Ordering::Less` meaning that `a` ranks above `b`
+pub fn compare(a: &PlayerStats, b: &PlayerStats, tiebreakers: &[Tiebreaker]) -> Ordering {
+    let score_ordering = b.score.cmp(&a.score);
+    tiebreakers
+        .iter()
+        .fold(score_ordering, |ordering, tiebreaker| {
+            ordering.then_with(|| match tiebreaker {
+                Tiebreaker::PerfectGuesses => b.perfect_guesses.cmp(&a.perfect_guesses),
+                Tiebreaker::TotalError => a.total_error_minutes.cmp(&b.total_error_minutes),
+                Tiebreaker::EarliestSubmission => match (a.last_submission, b.last_submission) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+            })
+        })
+}
+
+/// Standard competition ranks ("1224") of already sorted items
+pub fn competition_ranks<T>(sorted: &[T], is_tied: impl Fn(&T, &T) -> bool) -> Vec<u32> {
+    let mut ranks: Vec<u32> = Vec::with_capacity(sorted.len());
+    for (index, item) in sorted.iter().enumerate() {
+        let rank = match index {
+            0 => 1,
+            _ if is_tied(&sorted[index - 1], item) => ranks[index - 1],
+            _ => index as u32 + 1,
+        };
+        ranks.push(rank);
+    }
+    ranks
+}
+
+fn rank_with(
+    users: Vec<User>,
+    pictures: &[Picture],
+    tiebreakers: &[Tiebreaker],
+) -> Result<Vec<RankedPlayer>> {
+    let mut players = users
+        .into_iter()
+        .map(|user| -> Result<(User, PlayerStats)> {
+            let stats = PlayerStats::compute(&user, pictures)?;
+            Ok((user, stats))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    players.sort_by(|a, b| compare(&a.1, &b.1, tiebreakers));
+
+    let ranks = competition_ranks(&players, |a, b| {
+        compare(&a.1, &b.1, tiebreakers) == Ordering::Equal
+    });
+    Ok(players
+        .into_iter()
+        .zip(ranks)
+        .map(|((user, stats), rank)| RankedPlayer { rank, user, stats })
+        .collect())
+}
+
+/// Sorts and ranks the given users using the configured tiebreakers
+pub fn rank_players(users: Vec<User>) -> Result<Vec<RankedPlayer>> {
+    let tiebreakers = Config::get()?.leaderboard.tiebreakers;
+    let pictures = PictureMetaRepository::get_all_pictures()?;
+    rank_with(users, &pictures, &tiebreakers)
+}
+
+/// Whether a player is left out of the leaderboard by default
+pub fn is_hidden(user: &User) -> bool {
+    user.hidden || user.guess_data.is_empty()
+}
+
+/// Rankings of a set of players, the global ones being kept in memory until the users change
+#[derive(Debug)]
+pub struct Standings {
+    /// Every player, hidden ones included
+    pub all: Vec<RankedPlayer>,
+    /// Players shown on the leaderboard by default
+    pub visible: Vec<RankedPlayer>,
+}
+
+static STANDINGS: Mutex<Option<Arc<Standings>>> = Mutex::new(None);
+
+/// Drops the cached standings, must be called whenever users are written
+pub fn invalidate_standings() {
+    // the lock is held while computing, so standings computed from stale users are dropped too
+    *STANDINGS
+        .lock()
+        .expect("standings lock should not be poisoned") = None;
+}
+
+pub fn get_standings() -> Result<Arc<Standings>> {
+    let mut cached = STANDINGS
+        .lock()
+        .expect("standings lock should not be poisoned");
+    if let Some(standings) = cached.as_ref() {
+        return Ok(standings.clone());
+    }
+
+    let standings = Arc::new(compute_standings(UserRepository::get_all_users()?)?);
+    *cached = Some(standings.clone());
+    Ok(standings)
+}
+
+/// Ranks the given users, uncached, for subsets of the players such as leagues
+pub fn compute_standings(users: Vec<User>) -> Result<Standings> {
+    let visible_users = users.iter().filter(|u| !is_hidden(u)).cloned().collect();
+    Ok(Standings {
+        all: rank_players(users)?,
+        visible: rank_players(visible_users)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn stats(score: u32, perfect_guesses: u32, total_error_minutes: u32, day: u32) -> PlayerStats {
+        PlayerStats {
+            score,
+            perfect_guesses,
+            total_error_minutes,
+            last_submission: Some(Utc.with_ymd_and_hms(2025, 12, day, 12, 0, 0).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_competition_ranks() {
+        let scores = [500, 400, 400, 300, 300, 300, 100];
+        let ranks = competition_ranks(&scores, |a, b| a == b);
+        assert_eq!(vec![1, 2, 2, 4, 4, 4, 7], ranks);
+    }
+
+    #[test]
+    fn test_compare_applies_tiebreakers_in_order() {
+        let tiebreakers = [
+            Tiebreaker::PerfectGuesses,
+            Tiebreaker::TotalError,
+            Tiebreaker::EarliestSubmission,
+        ];
+
+        // score always comes first
+        let a = stats(300, 0, 900, 20);
+        let b = stats(200, 5, 10, 1);
+        assert_eq!(Ordering::Less, compare(&a, &b, &tiebreakers));
+
+        let a = stats(300, 1, 900, 20);
+        let b = stats(300, 0, 10, 1);
+        assert_eq!(Ordering::Less, compare(&a, &b, &tiebreakers));
+
+        let a = stats(300, 1, 900, 1);
+        let b = stats(300, 1, 10, 20);
+        assert_eq!(Ordering::Greater, compare(&a, &b, &tiebreakers));
+
+        let a = stats(300, 1, 10, 1);
+        let b = stats(300, 1, 10, 20);
+        assert_eq!(Ordering::Less, compare(&a, &b, &tiebreakers));
+
+        // without tiebreakers, equal scores are a tie
+        assert_eq!(Ordering::Equal, compare(&a, &b, &[]));
+    }
+}
@@ -0,0 +1,280 @@
+use anyhow::Result;
+use log::warn;
+use rtfw_http::http::HttpRequest;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::{BucketConfig, Config, RateLimitConfig},
+    http_helpers,
+};
+
+/// Full buckets carry no information, they are dropped once this many keys are tracked
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteGroup {
+    Guess,
+    Login,
+    Images,
+}
+
+impl RouteGroup {
+    fn bucket_config(&self, config: &RateLimitConfig) -> BucketConfig {
+        match self {
+            RouteGroup::Guess => config.guess,
+            RouteGroup::Login => config.login,
+            RouteGroup::Images => config.images,
+        }
+    }
+}
+
+impl fmt::Display for RouteGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RouteGroup::Guess => "guess",
+            RouteGroup::Login => "login",
+            RouteGroup::Images => "images",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: &BucketConfig, now: Instant) -> TokenBucket {
+        TokenBucket {
+            tokens: config.burst as f64,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, config: &BucketConfig, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * refill_rate(config)).min(config.burst as f64);
+        self.updated_at = now;
+    }
+
+    /// Takes a token, or tells how long to wait for the next one
+    fn try_take(&mut self, config: &BucketConfig, now: Instant) -> Result<(), Duration> {
+        self.refill(config, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / refill_rate(config),
+            ))
+        }
+    }
+
+    fn is_full(&self, config: &BucketConfig, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens + elapsed * refill_rate(config) >= config.burst as f64
+    }
+}
+
+/// Tokens per second
+fn refill_rate(config: &BucketConfig) -> f64 {
+    config.per_minute.max(1) as f64 / 60.0
+}
+
+#[derive(Debug, Default)]
+struct GroupCounters {
+    allowed: u64,
+    limited: u64,
+}
+
+#[derive(Default)]
+struct RateLimiter {
+    buckets: HashMap<(RouteGroup, String), TokenBucket>,
+    counters: HashMap<RouteGroup, GroupCounters>,
+}
+
+static RATE_LIMITER: LazyLock<Mutex<RateLimiter>> = LazyLock::new(Default::default);
+
+/// Address of the client as reported by the reverse proxy
+fn get_client_ip(request: &HttpRequest, trusted_proxy_hops: usize) -> String {
+    let forwarded_for = http_helpers::get_header(request, "X-Forwarded-For");
+    let real_ip = http_helpers::get_header(request, "X-Real-IP");
+    match get_forwarded_client_ip(forwarded_for, real_ip, trusted_proxy_hops) {
+        Some(ip) => ip.to_string(),
+        None => {
+            warn!("no client IP in the proxy headers, is the webapp reachable without the proxy?");
+            "unknown".to_string()
+        }
+    }
+}
+
+/// Clients can send their own `X-Forwarded-For` and each proxy appends the address it got the
+/// request from, so only the entries added by our proxies can be trusted. The client is the
+/// entry appended by the outermost of them, `trusted_proxy_hops` entries from the right.
+fn get_forwarded_client_ip<'a>(
+    forwarded_for: Option<&'a str>,
+    real_ip: Option<&'a str>,
+    trusted_proxy_hops: usize,
+) -> Option<&'a str> {
+    if trusted_proxy_hops == 0 {
+        return None;
+    }
+
+    let forwarded_ips: Vec<_> = forwarded_for
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .collect();
+    forwarded_ips
+        .get(forwarded_ips.len().saturating_sub(trusted_proxy_hops))
+        .copied()
+        .or_else(|| real_ip.map(str::trim).filter(|ip| !ip.is_empty()))
+}
+
+/// Counts a request of the client against the bucket of its IP. Returns how long to wait when
+/// the bucket is empty.
+pub fn check_ip(request: &HttpRequest, group: RouteGroup) -> Result<Option<Duration>> {
+    let trusted_proxy_hops = Config::get()?.rate_limit.trusted_proxy_hops;
+    check(
+        group,
+        format!("ip:{}", get_client_ip(request, trusted_proxy_hops)),
+    )
+}
+
+/// Counts a request against the bucket of the player, so that a player cannot get around the
+/// limit by switching networks
+pub fn check_user(user_id: &str, group: RouteGroup) -> Result<Option<Duration>> {
+    check(group, format!("user:{user_id}"))
+}
+
+fn check(group: RouteGroup, key: String) -> Result<Option<Duration>> {
+    let config = Config::get()?.rate_limit;
+    if !config.enabled {
+        return Ok(None);
+    }
+
+    let bucket_config = group.bucket_config(&config);
+    let now = Instant::now();
+    let mut limiter = RATE_LIMITER.lock().unwrap();
+    if limiter.buckets.len() >= MAX_TRACKED_BUCKETS {
+        limiter
+            .buckets
+            .retain(|(group, _), bucket| !bucket.is_full(&group.bucket_config(&config), now));
+    }
+
+    let outcome = limiter
+        .buckets
+        .entry((group, key.to_owned()))
+        .or_insert_with(|| TokenBucket::new(&bucket_config, now))
+        .try_take(&bucket_config, now);
+
+    let counters = limiter.counters.entry(group).or_default();
+    match outcome {
+        Ok(()) => {
+            counters.allowed += 1;
+            Ok(None)
+        }
+        Err(retry_after) => {
+            counters.limited += 1;
+            warn!(
+                "rate limited {group} request of {key}, retry in {:.1}s ({} limited / {} allowed {group} requests so far)",
+                retry_after.as_secs_f64(),
+                counters.limited,
+                counters.allowed
+            );
+            Ok(Some(retry_after))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: BucketConfig = BucketConfig {
+        burst: 3,
+        per_minute: 6,
+    };
+
+    #[test]
+    fn test_token_bucket_burst() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(&CONFIG, now);
+        for _ in 0..3 {
+            assert_eq!(bucket.try_take(&CONFIG, now), Ok(()));
+        }
+        assert_eq!(bucket.try_take(&CONFIG, now), Err(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_token_bucket_refill() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(&CONFIG, now);
+        for _ in 0..3 {
+            bucket.try_take(&CONFIG, now).unwrap();
+        }
+        assert!(!bucket.is_full(&CONFIG, now));
+
+        let later = now + Duration::from_secs(10);
+        assert_eq!(bucket.try_take(&CONFIG, later), Ok(()));
+        assert!(bucket.try_take(&CONFIG, later).is_err());
+
+        // never refills past the burst
+        let much_later = later + Duration::from_secs(3600);
+        assert!(bucket.is_full(&CONFIG, much_later));
+        for _ in 0..3 {
+            assert_eq!(bucket.try_take(&CONFIG, much_later), Ok(()));
+        }
+        assert!(bucket.try_take(&CONFIG, much_later).is_err());
+    }
+
+    #[test]
+    fn test_get_forwarded_client_ip() {
+        let forwarded_for = Some("198.51.100.1, 203.0.113.7");
+        assert_eq!(
+            Some("203.0.113.7"),
+            get_forwarded_client_ip(forwarded_for, None, 1)
+        );
+        assert_eq!(
+            Some("198.51.100.1"),
+            get_forwarded_client_ip(forwarded_for, None, 2)
+        );
+        assert_eq!(
+            Some("203.0.113.7"),
+            get_forwarded_client_ip(Some("203.0.113.7"), None, 2)
+        );
+        assert_eq!(
+            Some("203.0.113.7"),
+            get_forwarded_client_ip(None, Some(" 203.0.113.7 "), 1)
+        );
+        assert_eq!(None, get_forwarded_client_ip(forwarded_for, None, 0));
+        assert_eq!(None, get_forwarded_client_ip(None, None, 1));
+    }
+
+    #[test]
+    fn test_forged_forwarded_for_is_still_limited() {
+        let burst = Config::get().unwrap().rate_limit.guess.burst;
+        let mut outcomes = (0..=burst).map(|i| {
+            // the client makes up a new address each time, the proxy appends the real one
+            let forwarded_for = format!("10.0.0.{i}, 192.0.2.44");
+            let ip = get_forwarded_client_ip(Some(&forwarded_for), None, 1).unwrap();
+            check(RouteGroup::Guess, format!("ip:{ip}")).unwrap()
+        });
+
+        assert!(
+            outcomes
+                .by_ref()
+                .take(burst as usize)
+                .all(|outcome| outcome.is_none())
+        );
+        assert!(outcomes.next().unwrap().is_some());
+    }
+}
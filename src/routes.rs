@@ -1,18 +1,20 @@
 use anyhow::Result;
 use chrono::Utc;
-use log::debug;
+use log::{debug, warn};
 use rand::seq::IndexedRandom;
 use rtfw_http::http::response_status_codes::HttpStatusCode;
-use rtfw_http::http::{HttpRequest, HttpResponse, HttpResponseBuilder};
+use rtfw_http::http::{HttpMethod, HttpRequest, HttpResponse};
 use rtfw_http::router::RoutingData;
-use rust_i18n::t;
 use serde::Serialize;
 use serde_json::json;
-use std::fs;
+use utoipa::ToSchema;
 
+use crate::config::Config;
+use crate::database::user_repository::UserRepository;
+use crate::locale::Locale;
 use crate::models::user::User;
 use crate::utils::Day;
-use crate::{http_helpers, utils};
+use crate::{controllers, embedded, http_helpers, markdown, ranking, templates, utils};
 
 pub fn get_index(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
     let user = http_helpers::get_logged_in_user(request)?;
@@ -25,16 +27,20 @@ pub fn get_index(request: &HttpRequest, _routing_data: &RoutingData) -> Result<H
     let game_ended = utils::is_game_over();
     let game_ends_soon = !game_ended && utils::get_days_remaining() <= 5;
 
-    let winner = if game_ended {
-        utils::get_ranked_players_sorted()?
-            .first()
-            .cloned()
-            .map(|u| u.username)
+    // every player tied for the first place shares the win
+    let winners: Vec<_> = if game_ended {
+        ranking::get_standings()?
+            .visible
+            .iter()
+            .take_while(|p| p.rank == 1)
+            .map(|p| p.user.username.to_owned())
+            .collect()
     } else {
-        None
+        vec![]
     };
 
     let greet_msg = format!("Hello {}!", name);
+    let teams_enabled = Config::get()?.teams.enabled;
 
     let data = json!({
         "authenticated": authenticated,
@@ -42,53 +48,23 @@ pub fn get_index(request: &HttpRequest, _routing_data: &RoutingData) -> Result<H
         "days": get_calendar_entries(user.as_ref()),
         "gameEnded": game_ended,
         "gameEndsSoon": game_ends_soon,
-        "winnerName": winner,
-        "i18n": I18n::from_request(request).unwrap(),
+        "winnerNames": winners.join(", "),
+        "winnersTied": winners.len() > 1,
+        "teamsEnabled": teams_enabled,
     });
-    let rendered = utils::render_view("index", &data)?;
-    HttpResponseBuilder::new().set_html_body(&rendered).build()
+    let rendered = templates::render(request, "index", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
 }
 
-#[derive(Serialize)]
-struct I18n {
-    title: String,
-    edition: String,
-    about: String,
-    profile: String,
-    logout: String,
-    login: String,
-    leaderboard: String,
-    next_unlock: String,
-    game_ends_soon_text: String,
-    game_over_text: String,
-}
-
-impl I18n {
-    fn from_request(request: &HttpRequest) -> Result<I18n> {
-        let user_locale = http_helpers::get_user_locale(request)?.to_str();
-        Ok(I18n {
-            title: t!("title", locale = user_locale).to_string(),
-            edition: t!("edition", locale = user_locale).to_string(),
-            about: t!("index.about", locale = user_locale).to_string(),
-            profile: t!("index.profile", locale = user_locale).to_string(),
-            logout: t!("index.logout", locale = user_locale).to_string(),
-            login: t!("index.login", locale = user_locale).to_string(),
-            leaderboard: t!("index.leaderboard", locale = user_locale).to_string(),
-            next_unlock: t!("index.next_unlock", locale = user_locale).to_string(),
-            game_ends_soon_text: t!("index.game_ends_soon_text", locale = user_locale).to_string(),
-            game_over_text: t!("index.game_over_text", locale = user_locale).to_string(),
-        })
-    }
-}
-
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CalendarEntry {
+    #[schema(value_type = u32)]
     pub day: Day,
     pub released: bool,
     pub guessed: bool,
 }
 
-fn get_calendar_entries(user: Option<&User>) -> Vec<CalendarEntry> {
+pub fn get_calendar_entries(user: Option<&User>) -> Vec<CalendarEntry> {
     let utc_now = Utc::now();
     (1..=25)
         .map(|day| CalendarEntry {
@@ -104,33 +80,119 @@ fn get_calendar_entries(user: Option<&User>) -> Vec<CalendarEntry> {
 }
 
 pub fn get_about(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
-    let user_locale = http_helpers::get_user_locale(request)?;
-    let view_name = match user_locale {
-        http_helpers::Locale::French => "about_fr",
-        _ => "about",
+    let locale = http_helpers::get_user_locale(request)?;
+    let content_path = embedded::localized_path("src/assets/about.md", locale);
+    let data = json!({
+        "content": markdown::to_html(&embedded::read_to_string(&content_path)?),
+    });
+    let rendered = templates::render(request, "about", &data)?;
+    http_helpers::response().set_html_body(&rendered).build()
+}
+
+/// Form of the language switcher, the choice is kept in a cookie and saved for logged in users
+pub fn post_locale(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+    // otherwise any other site could change the language, and the saved preference, of a player
+    if !http_helpers::has_valid_optional_csrf_token(request) {
+        warn!("rejected language change: missing or invalid CSRF token");
+        return http_helpers::forbidden();
+    }
+
+    let locale = url::form_urlencoded::parse(&request.body)
+        .find(|(key, _)| key == "locale")
+        .and_then(|(_, value)| Locale::from_code(&value));
+    let Some(locale) = locale else {
+        return http_helpers::bad_request_msg("Unsupported language");
     };
-    let body = utils::load_view(view_name)?;
-    HttpResponseBuilder::new().set_html_body(&body).build()
+
+    if let Some(mut user) = http_helpers::get_logged_in_user(request)? {
+        user.preferred_locale = Some(locale.as_str().to_string());
+        UserRepository::update_user(user)?;
+    }
+
+    let location = http_helpers::get_header(request, "Referer")
+        .and_then(get_same_site_path)
+        .unwrap_or_else(|| "/".to_string());
+    http_helpers::response()
+        .set_status(HttpStatusCode::Found)
+        .set_header("Set-Cookie", &http_helpers::create_locale_cookie(locale))
+        .set_header("Location", &location)
+        .build()
 }
 
-pub fn catcher_get_404(
-    _request: &HttpRequest,
-    _routing_data: &RoutingData,
-) -> Result<HttpResponse> {
-    let catchphrases: Vec<_> = fs::read_to_string("src/assets/404_phrases.md")?
+/// Path and query of the page the switcher was used on, so that only this site is redirected to.
+/// Leading slashes are collapsed as `//host` would lead to another site.
+fn get_same_site_path(referer: &str) -> Option<String> {
+    let url = url::Url::parse(referer).ok()?;
+    let path = format!("/{}", url.path().trim_start_matches('/'));
+    match url.query() {
+        Some(query) => Some(format!("{path}?{query}")),
+        None => Some(path),
+    }
+}
+
+pub fn catcher_get_404(request: &HttpRequest, _routing_data: &RoutingData) -> Result<HttpResponse> {
+    let locale = http_helpers::get_user_locale(request)?;
+    let phrases_path = embedded::localized_path("src/assets/404_phrases.md", locale);
+    let catchphrases: Vec<_> = embedded::read_to_string(&phrases_path)?
         .lines()
         .map(String::from)
         .collect();
 
     let phrase = catchphrases.choose(&mut rand::rng()).unwrap();
-    let rendered_phrase = utils::markdown_to_html(phrase)?;
+    let rendered_phrase = markdown::inline_to_html(phrase);
     debug!(
         "someone got lost, giving them the catch all route and a catchphrase: {rendered_phrase}"
     );
-    let rendered = utils::render_view("404", &json!({"catchphrase": rendered_phrase}))?;
+    let rendered = templates::render(request, "404", &json!({"catchphrase": rendered_phrase}))?;
 
-    HttpResponseBuilder::new()
+    http_helpers::response()
         .set_status(HttpStatusCode::NotFound)
         .set_html_body(&rendered)
         .build()
 }
+
+fn is_api_path(url: &str) -> bool {
+    let path = url.split('?').next().unwrap_or_default();
+    path == "/api" || path.starts_with("/api/")
+}
+
+/// Requests no route matched, whatever their method. API clients get a JSON error, browsers the
+/// 404 page.
+pub fn catcher_404(request: &HttpRequest, routing_data: &RoutingData) -> Result<HttpResponse> {
+    if is_api_path(&request.url) {
+        return controllers::api::catcher_api_404(request, routing_data);
+    }
+
+    match request.method {
+        HttpMethod::GET => catcher_get_404(request, routing_data),
+        _ => http_helpers::response()
+            .set_status(HttpStatusCode::NotFound)
+            .build(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_same_site_path() {
+        assert_eq!(
+            Some("/day/3?lang=fr"),
+            get_same_site_path("https://aot.coko7.fr/day/3?lang=fr").as_deref()
+        );
+        assert_eq!(
+            Some("/evil.com/"),
+            get_same_site_path("https://aot.coko7.fr//evil.com/").as_deref()
+        );
+        assert_eq!(None, get_same_site_path("/relative"));
+    }
+
+    #[test]
+    fn test_is_api_path() {
+        assert!(is_api_path("/api/v1/unknown"));
+        assert!(is_api_path("/api?page=2"));
+        assert!(!is_api_path("/apiary"));
+        assert!(!is_api_path("/day/3"));
+    }
+}
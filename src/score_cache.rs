@@ -0,0 +1,109 @@
+use anyhow::Result;
+use chrono::Utc;
+use log::info;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::{Config, ScoreConfig},
+    database::{
+        picture_meta_repository::PictureMetaRepository,
+        score_cache_repository::{ScoreCacheRepository, ScoreCacheState},
+        user_repository::UserRepository,
+    },
+    models::picture::Picture,
+    utils,
+};
+
+/// Identifies the inputs of the cached points: the scoring configuration and the picture times.
+/// Both are serialized and hashed with SHA-256, so the fingerprint is stable across toolchains.
+pub fn compute_fingerprint(score_config: &ScoreConfig, pictures: &[Picture]) -> Result<String> {
+    let mut picture_times: Vec<_> = pictures
+        .iter()
+        .map(|p| (p.day(), p.time_taken.as_str()))
+        .collect();
+    picture_times.sort();
+
+    let inputs = serde_json::to_string(&(score_config, picture_times))?;
+    Ok(format!("{:x}", Sha256::digest(inputs.as_bytes())))
+}
+
+/// Recomputes the cached points of every guess of every user
+pub fn recompute_all_scores() -> Result<()> {
+    let pictures = PictureMetaRepository::get_all_pictures()?;
+    let mut users = UserRepository::get_all_users()?;
+
+    let mut guesses = 0;
+    for user in users.iter_mut() {
+        for (day, guess) in user.guess_data.iter_mut() {
+            guess.points = match pictures.iter().find(|p| p.day() == *day) {
+                Some(picture) => Some(utils::compute_score(picture, guess.hm)?),
+                None => None,
+            };
+            guesses += 1;
+        }
+    }
+
+    UserRepository::update_all_users(users)?;
+    ScoreCacheRepository::set_state(ScoreCacheState {
+        fingerprint: compute_fingerprint(&Config::get()?.score, &pictures)?,
+        computed_at: Utc::now(),
+    })?;
+    info!("recomputed the cached points of {guesses} guesses");
+    Ok(())
+}
+
+/// Forces a recompute on the next call to `ensure_scores_up_to_date`
+pub fn invalidate() -> Result<()> {
+    ScoreCacheRepository::clear_state()
+}
+
+/// Recomputes the cached points if the pictures or the scoring changed since they were computed
+pub fn ensure_scores_up_to_date() -> Result<bool> {
+    let pictures = PictureMetaRepository::get_all_pictures()?;
+    let fingerprint = compute_fingerprint(&Config::get()?.score, &pictures)?;
+    let is_stale = match ScoreCacheRepository::get_state()? {
+        Some(state) => state.fingerprint != fingerprint,
+        None => true,
+    };
+
+    if is_stale {
+        info!("scoring inputs changed, recomputing cached points");
+        recompute_all_scores()?;
+    }
+    Ok(is_stale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ScoringCurve;
+
+    #[test]
+    fn test_fingerprint_changes_with_inputs() {
+        let config = ScoreConfig {
+            max_reward: 200.0,
+            curve: ScoringCurve::Linear { divider: 1440 },
+        };
+        let pictures = vec![Picture::for_test(1, "12:00"), Picture::for_test(2, "08:30")];
+        let fingerprint = compute_fingerprint(&config, &pictures).unwrap();
+
+        // picture order does not matter
+        let reversed: Vec<_> = pictures.iter().rev().cloned().collect();
+        assert_eq!(
+            fingerprint,
+            compute_fingerprint(&config, &reversed).unwrap()
+        );
+
+        let moved = vec![Picture::for_test(1, "12:00"), Picture::for_test(2, "08:31")];
+        assert_ne!(fingerprint, compute_fingerprint(&config, &moved).unwrap());
+
+        let other_config = ScoreConfig {
+            max_reward: 100.0,
+            curve: ScoringCurve::Linear { divider: 1440 },
+        };
+        assert_ne!(
+            fingerprint,
+            compute_fingerprint(&other_config, &pictures).unwrap()
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use anyhow::Result;
+
+use crate::config::{Config, ScoreConfig, ScoreStep, ScoringCurve};
+
+/// Number of minutes in a day, which is also the biggest possible guess difference
+pub const MINUTES_IN_DAY: u32 = 24 * 60;
+
+pub trait ScoringStrategy {
+    /// Name of the curve, as written in `config.toml`
+    fn name(&self) -> &'static str;
+    /// Points awarded for a guess that is `diff_minutes` away from the real time
+    fn points(&self, diff_minutes: u32) -> u32;
+}
+
+pub struct LinearCurve {
+    pub max_reward: f64,
+    pub divider: u32,
+}
+
+impl ScoringStrategy for LinearCurve {
+    fn name(&self) -> &'static str {
+        "linear"
+    }
+
+    fn points(&self, diff_minutes: u32) -> u32 {
+        let ratio = diff_minutes as f64 / self.divider as f64;
+        let result = self.max_reward * (1.0 - ratio);
+        result.max(0.0) as u32
+    }
+}
+
+pub struct PowerCurve {
+    pub max_reward: f64,
+    pub exponent: f64,
+    pub divider: u32,
+}
+
+impl ScoringStrategy for PowerCurve {
+    fn name(&self) -> &'static str {
+        "power"
+    }
+
+    fn points(&self, diff_minutes: u32) -> u32 {
+        let ratio = diff_minutes as f64 / self.divider as f64;
+        let result = self.max_reward * (1.0 - ratio.powf(self.exponent));
+        result.max(0.0) as u32 // Clamp negative points to zero
+    }
+}
+
+pub struct GaussianCurve {
+    pub max_reward: f64,
+    pub sigma: f64,
+}
+
+impl ScoringStrategy for GaussianCurve {
+    fn name(&self) -> &'static str {
+        "gaussian"
+    }
+
+    fn points(&self, diff_minutes: u32) -> u32 {
+        let diff = diff_minutes as f64;
+        let result = self.max_reward * (-(diff * diff) / (2.0 * self.sigma * self.sigma)).exp();
+        result.max(0.0) as u32
+    }
+}
+
+pub struct SteppedCurve {
+    pub max_reward: f64,
+    pub steps: Vec<ScoreStep>,
+}
+
+impl ScoringStrategy for SteppedCurve {
+    fn name(&self) -> &'static str {
+        "stepped"
+    }
+
+    fn points(&self, diff_minutes: u32) -> u32 {
+        // Steps may be written in any order, the tightest matching one wins
+        let ratio = self
+            .steps
+            .iter()
+            .filter(|step| diff_minutes <= step.within)
+            .min_by_key(|step| step.within)
+            .map_or(0.0, |step| step.ratio);
+        (self.max_reward * ratio).max(0.0) as u32
+    }
+}
+
+pub fn from_config(config: &ScoreConfig) -> Box<dyn ScoringStrategy> {
+    let max_reward = config.max_reward;
+    match config.curve.clone() {
+        ScoringCurve::Linear { divider } => Box::new(LinearCurve {
+            max_reward,
+            divider,
+        }),
+        ScoringCurve::Power { exponent, divider } => Box::new(PowerCurve {
+            max_reward,
+            exponent,
+            divider,
+        }),
+        ScoringCurve::Gaussian { sigma } => Box::new(GaussianCurve { max_reward, sigma }),
+        ScoringCurve::Stepped { steps } => Box::new(SteppedCurve { max_reward, steps }),
+    }
+}
+
+pub fn get_active_strategy() -> Result<Box<dyn ScoringStrategy>> {
+    let config = Config::get()?;
+    Ok(from_config(&config.score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_curve_halfway_gives_half() {
+        let curve = LinearCurve {
+            max_reward: 200.0,
+            divider: MINUTES_IN_DAY,
+        };
+        assert_eq!(200, curve.points(0));
+        assert_eq!(100, curve.points(12 * 60));
+        assert_eq!(0, curve.points(MINUTES_IN_DAY));
+    }
+
+    #[test]
+    fn test_power_curve_matches_legacy_formula() {
+        let curve = PowerCurve {
+            max_reward: 200.0,
+            exponent: 0.75,
+            divider: MINUTES_IN_DAY,
+        };
+        assert_eq!(200, curve.points(0));
+        assert_eq!(178, curve.points(75));
+        assert_eq!(81, curve.points(12 * 60));
+    }
+
+    #[test]
+    fn test_gaussian_curve_decreases() {
+        let curve = GaussianCurve {
+            max_reward: 200.0,
+            sigma: 120.0,
+        };
+        assert_eq!(200, curve.points(0));
+        assert_eq!(121, curve.points(120));
+        assert_eq!(0, curve.points(12 * 60));
+    }
+
+    #[test]
+    fn test_stepped_curve_uses_tightest_bucket() {
+        let curve = SteppedCurve {
+            max_reward: 200.0,
+            steps: vec![
+                ScoreStep {
+                    within: 30,
+                    ratio: 0.5,
+                },
+                ScoreStep {
+                    within: 5,
+                    ratio: 1.0,
+                },
+            ],
+        };
+        assert_eq!(200, curve.points(5));
+        assert_eq!(100, curve.points(6));
+        assert_eq!(0, curve.points(31));
+    }
+}
@@ -1,39 +1,42 @@
 document.addEventListener("DOMContentLoaded", function () {
   // const statusElem = document.getElementById("status");
-  const formElem = document.querySelector("form");
+  const formElem = document.querySelector("form#guess-daily-picture");
   const guessElem = document.querySelector("input#time-guess");
   const dayToken = document.querySelector('input[name="day-token"]');
+  const jokerElem = document.querySelector("input#joker");
 
   formElem.addEventListener("submit", function (event) {
     event.preventDefault();
-    const guessValue = guessElem.value;
+    const day = parseInt(dayToken.value);
     const data = {
-      day: parseInt(dayToken.value),
-      guess: guessValue,
+      guess: guessElem.value,
+      joker: jokerElem ? jokerElem.checked : false,
     };
 
-    fetch("/guess", {
+    fetch(`/guess/${day}`, {
       method: "POST",
       headers: {
         "Content-Type": "application/json",
+        "X-CSRF-Token": getCsrfToken(),
       },
       body: JSON.stringify(data),
     })
-      .then((response) => {
+      .then(async (response) => {
         if (response.status === 200) {
           return response.json();
-        } else {
-          throw new Error("got an error");
         }
+        const body = await response.json().catch(() => null);
+        throw new Error(body?.error?.message ?? `got an error (${response.status})`);
       })
       .then((data) => {
         // const time = getTimeLabel();
         const points = data.points;
-        alert(`${points} points!`);
+        const badges = data.badges.join(" ");
+        alert(badges ? `${points} points! New badges: ${badges}` : `${points} points!`);
         globalThis.location.reload();
       })
       .catch((error) => {
-        alert(`Invalid input dawg: ${error}`);
+        alert(error.message);
         // statusElem.innerHTML =
         //   `[${time}] ERROR: Failed to upload data to server: ` + error.message;
       });
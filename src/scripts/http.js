@@ -0,0 +1,24 @@
+function postJson(url, data) {
+  return fetch(url, {
+    method: "POST",
+    headers: {
+      "Content-Type": "application/json",
+      "X-CSRF-Token": getCsrfToken(),
+    },
+    body: JSON.stringify(data),
+  }).then((response) => {
+    if (response.status !== 200) {
+      return response.text().then((text) => {
+        throw new Error(text || response.status);
+      });
+    }
+
+    const contentType = response.headers.get("Content-Type") || "";
+    return contentType.includes("json") ? response.json() : {};
+  });
+}
+
+function getCsrfToken() {
+  const metaElem = document.querySelector('meta[name="csrf-token"]');
+  return metaElem ? metaElem.content : "";
+}
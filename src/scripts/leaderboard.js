@@ -1,19 +1,20 @@
 document.addEventListener("DOMContentLoaded", function() {
   const checkboxShowHidden = document.querySelector("#chk-show-hidden-players");
-  const playerRows = document.querySelectorAll("tr");
+  const playerRows = document.querySelectorAll("tr[data-rank-all]");
 
+  // ranks are computed by the server, ties included, for both views
   function toggleHiddenPlayers() {
     const showHidden = checkboxShowHidden.checked;
-    let index = 1;
 
     playerRows.forEach((playerRow) => {
       removeClassesStartingWith(playerRow, "rank-");
       const isHiddenPlayer = playerRow.classList.contains("hidden");
       if (!isHiddenPlayer || showHidden) {
         playerRow.style.display = "";
+        const rank = showHidden ? playerRow.dataset.rankAll : playerRow.dataset.rank;
         const rankCell = playerRow.querySelector("td.rank");
-        playerRow.classList.add(`rank-${index}`);
-        if (rankCell) rankCell.textContent = index++;
+        playerRow.classList.add(`rank-${rank}`);
+        if (rankCell) rankCell.textContent = rank;
       } else {
         playerRow.style.display = "none";
       }
@@ -22,8 +23,44 @@ document.addEventListener("DOMContentLoaded", function() {
 
   checkboxShowHidden.addEventListener("change", toggleHiddenPlayers);
   toggleHiddenPlayers(); // Initial state
+
+  document.querySelectorAll("th.sortable").forEach((header) => {
+    header.addEventListener("click", () => sortRows(header));
+  });
 });
 
+// Sorts the player rows by the column of the given header, clicking again reverses the order
+function sortRows(header) {
+  const table = header.closest("table");
+  const column = header.dataset.sort;
+  const ascending = header.dataset.order === "asc";
+
+  const rows = Array.from(table.querySelectorAll("tr[data-rank-all]"));
+  rows.sort((a, b) => {
+    const valueA = getSortValue(a, column);
+    const valueB = getSortValue(b, column);
+    // players without a value always go last
+    if (valueA === null || valueB === null) {
+      return (valueA === null) - (valueB === null);
+    }
+    return ascending ? valueA - valueB : valueB - valueA;
+  });
+  rows.forEach((row) => row.parentNode.appendChild(row));
+
+  table.querySelectorAll("th.sortable").forEach((th) => th.classList.remove("sorted"));
+  header.classList.add("sorted");
+  header.dataset.order = ascending ? "desc" : "asc";
+}
+
+function getSortValue(row, column) {
+  if (column === "rank") {
+    return parseInt(row.dataset.rankAll);
+  }
+
+  const value = row.querySelector(`td.${column}`).dataset.value;
+  return value === "" ? null : parseFloat(value);
+}
+
 function removeClassesStartingWith(element, prefix) {
   const classes = element.className.split(" ");
   const filteredClasses = classes.filter(
@@ -0,0 +1,43 @@
+document.addEventListener("DOMContentLoaded", function () {
+  const createFormElem = document.querySelector("form#create-league");
+  const joinFormElem = document.querySelector("form#join-league");
+  const leagueToken = document.querySelector('input[name="league-token"]');
+
+  if (createFormElem) {
+    createFormElem.addEventListener("submit", function (event) {
+      event.preventDefault();
+      const name = document.querySelector("input#league-name").value;
+      postJson("/leagues", { name })
+        .then((data) => (globalThis.location.href = `/leagues/${data.id}`))
+        .catch((error) => alert(`Failed to create league: ${error}`));
+    });
+  }
+
+  if (joinFormElem) {
+    joinFormElem.addEventListener("submit", function (event) {
+      event.preventDefault();
+      const inviteCode = document.querySelector("input#invite-code").value;
+      postJson("/leagues/join", { invite_code: inviteCode })
+        .then((data) => (globalThis.location.href = `/leagues/${data.id}`))
+        .catch((error) => alert(`Failed to join league: ${error}`));
+    });
+  }
+
+  document.querySelectorAll("button.remove-member").forEach((buttonElem) => {
+    buttonElem.addEventListener("click", function () {
+      const userId = buttonElem.dataset.userId;
+      postJson(`/leagues/${leagueToken.value}/remove`, { user_id: userId })
+        .then(() => globalThis.location.reload())
+        .catch((error) => alert(`Failed to remove member: ${error}`));
+    });
+  });
+
+  const leaveElem = document.querySelector("button#leave-league");
+  if (leaveElem) {
+    leaveElem.addEventListener("click", function () {
+      postJson(`/leagues/${leagueToken.value}/leave`, {})
+        .then(() => (globalThis.location.href = "/leagues"))
+        .catch((error) => alert(`Failed to leave league: ${error}`));
+    });
+  }
+});
@@ -0,0 +1,30 @@
+document.addEventListener("DOMContentLoaded", function () {
+  const createTokenFormElem = document.querySelector("form#create-token");
+  const createdTokenElem = document.querySelector("p#created-token");
+
+  if (createTokenFormElem) {
+    createTokenFormElem.addEventListener("submit", function (event) {
+      event.preventDefault();
+      const name = document.querySelector("input#token-name").value;
+      const scope = document.querySelector("select#token-scope").value;
+      postJson("/auth/me/tokens", { name, scope })
+        .then((data) => {
+          // the token is only shown once, it is not stored in clear on the server
+          createdTokenElem.querySelector("span").textContent = createdTokenElem.dataset.message;
+          createdTokenElem.querySelector("code").textContent = data.token;
+          createdTokenElem.hidden = false;
+          createTokenFormElem.reset();
+        })
+        .catch((error) => alert(`Failed to create token: ${error}`));
+    });
+  }
+
+  document.querySelectorAll("button.revoke-token").forEach((buttonElem) => {
+    buttonElem.addEventListener("click", function () {
+      const tokenId = buttonElem.dataset.tokenId;
+      postJson(`/auth/me/tokens/${tokenId}/revoke`, {})
+        .then(() => globalThis.location.reload())
+        .catch((error) => alert(`Failed to revoke token: ${error}`));
+    });
+  });
+});
@@ -0,0 +1,35 @@
+document.addEventListener("DOMContentLoaded", function () {
+  const createFormElem = document.querySelector("form#create-team");
+  const assignFormElem = document.querySelector("form#assign-member");
+  const joinElem = document.querySelector("button#join-team");
+  const teamToken = document.querySelector('input[name="team-token"]');
+
+  if (createFormElem) {
+    createFormElem.addEventListener("submit", function (event) {
+      event.preventDefault();
+      const name = document.querySelector("input#team-name").value;
+      postJson("/teams", { name })
+        .then(() => globalThis.location.reload())
+        .catch((error) => alert(`Failed to create team: ${error}`));
+    });
+  }
+
+  if (assignFormElem) {
+    assignFormElem.addEventListener("submit", function (event) {
+      event.preventDefault();
+      const username = document.querySelector("input#member-username").value;
+      postJson(`/teams/${teamToken.value}/members`, { username })
+        .then(() => globalThis.location.reload())
+        .catch((error) => alert(`Failed to assign player: ${error}`));
+    });
+  }
+
+  if (joinElem) {
+    joinElem.addEventListener("click", function () {
+      const teamId = document.querySelector("select#team-select").value;
+      postJson(`/teams/${teamId}/join`, {})
+        .then(() => globalThis.location.reload())
+        .catch((error) => alert(`Failed to join team: ${error}`));
+    });
+  }
+});
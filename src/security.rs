@@ -1,9 +1,15 @@
 use crate::{
     config::{Config, OAuth2Config},
     models::user::User,
+    utils,
 };
 use anyhow::{Context, Result, bail};
 use chrono::Utc;
+use sha2::{Digest, Sha256};
+
+/// Makes API tokens easy to recognize, e.g. by secret scanners
+pub const API_TOKEN_PREFIX: &str = "aot_";
+const API_TOKEN_LENGTH: usize = 40;
 
 pub fn has_access_token_expired(user: &User) -> Result<bool> {
     let expires_at = user
@@ -21,3 +27,48 @@ pub fn get_oauth2_provider_config(provider_name: &str) -> Result<OAuth2Config> {
         _ => bail!("unsupported IdP: {provider_name}"),
     })
 }
+
+pub fn generate_api_token() -> String {
+    format!(
+        "{API_TOKEN_PREFIX}{}",
+        utils::generate_code(API_TOKEN_LENGTH)
+    )
+}
+
+/// Only the hash of API tokens is stored, so that a leaked database does not leak tokens
+pub fn hash_api_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Synchronizer token of a session: only pages served to the owner of the bearer cookie know it,
+/// so it cannot be forged by a third-party page submitting requests on their behalf
+pub fn get_csrf_token(bearer: &str) -> String {
+    format!("{:x}", Sha256::digest(format!("csrf:{bearer}").as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_api_token() {
+        let token = generate_api_token();
+        assert!(token.starts_with(API_TOKEN_PREFIX));
+        assert_eq!(hash_api_token(&token), hash_api_token(&token));
+        assert_ne!(
+            hash_api_token(&token),
+            hash_api_token(&generate_api_token())
+        );
+        assert_eq!(
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            hash_api_token("abc")
+        );
+    }
+
+    #[test]
+    fn test_get_csrf_token() {
+        assert_eq!(get_csrf_token("bearer"), get_csrf_token("bearer"));
+        assert_ne!(get_csrf_token("bearer"), get_csrf_token("other-bearer"));
+        assert_ne!(get_csrf_token("bearer"), hash_api_token("bearer"));
+    }
+}
@@ -0,0 +1,108 @@
+use rtfw_http::http::HttpResponseBuilder;
+use std::sync::LazyLock;
+
+use crate::config::{Config, SecurityHeadersConfig};
+
+/// The config is embedded in the binary, so the headers never change while running
+static SECURITY_HEADERS: LazyLock<Vec<(&'static str, String)>> = LazyLock::new(|| {
+    let config = Config::get()
+        .expect("config should be valid")
+        .security_headers;
+    get_security_headers(&config)
+});
+
+fn get_security_headers(config: &SecurityHeadersConfig) -> Vec<(&'static str, String)> {
+    let mut headers = vec![
+        (
+            "Content-Security-Policy",
+            config.content_security_policy.to_owned(),
+        ),
+        ("X-Content-Type-Options", "nosniff".to_string()),
+        ("Referrer-Policy", config.referrer_policy.to_owned()),
+        ("X-Frame-Options", config.frame_options.to_owned()),
+    ];
+
+    if config.behind_tls {
+        headers.push((
+            "Strict-Transport-Security",
+            format!("max-age={}; includeSubDomains", config.hsts_max_age),
+        ));
+    }
+
+    headers
+}
+
+/// Adds the security headers to a response, see `http_helpers::response`
+pub fn apply(builder: HttpResponseBuilder) -> HttpResponseBuilder {
+    SECURITY_HEADERS
+        .iter()
+        .fold(builder, |builder, (name, value)| {
+            builder.set_header(name, value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    fn get_config(behind_tls: bool) -> SecurityHeadersConfig {
+        SecurityHeadersConfig {
+            content_security_policy: "default-src 'self'".to_string(),
+            referrer_policy: "same-origin".to_string(),
+            frame_options: "DENY".to_string(),
+            behind_tls,
+            hsts_max_age: 60,
+        }
+    }
+
+    #[test]
+    fn test_hsts_only_behind_tls() {
+        let headers = get_security_headers(&get_config(false));
+        assert!(
+            headers
+                .iter()
+                .all(|(name, _)| *name != "Strict-Transport-Security")
+        );
+        assert!(headers.contains(&("X-Content-Type-Options", "nosniff".to_string())));
+
+        let headers = get_security_headers(&get_config(true));
+        assert!(headers.contains(&(
+            "Strict-Transport-Security",
+            "max-age=60; includeSubDomains".to_string()
+        )));
+    }
+
+    fn collect_rust_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                collect_rust_files(&path, files);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                files.push(path);
+            }
+        }
+    }
+
+    /// Responses must be created with `http_helpers::response` to carry the security headers
+    #[test]
+    fn test_responses_use_security_headers() {
+        let src_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut files = vec![];
+        collect_rust_files(&src_dir, &mut files);
+
+        let offenders: Vec<_> = files
+            .iter()
+            .filter(|path| !path.ends_with("http_helpers.rs"))
+            .filter(|path| {
+                fs::read_to_string(path)
+                    .unwrap()
+                    .contains(concat!("HttpResponseBuilder", "::new()"))
+            })
+            .collect();
+        assert!(
+            offenders.is_empty(),
+            "responses built without security headers in: {offenders:?}"
+        );
+    }
+}
@@ -0,0 +1,511 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::DateTime;
+use handlebars::{
+    Context as HandlebarsContext, Handlebars, Helper, HelperDef, RenderContext, RenderError,
+    RenderErrorReason, ScopedJson, handlebars_helper,
+};
+use log::debug;
+use rtfw_http::http::HttpRequest;
+use rust_i18n::t;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::sync::LazyLock;
+
+use crate::{config::Config, controllers::assets, embedded, http_helpers, locale::Locale};
+
+const VIEWS_DIR: &str = "src/views";
+
+/// Every view, layout and partial is compiled once, the first time a page is rendered
+static TEMPLATES: LazyLock<Result<Handlebars<'static>>> = LazyLock::new(|| {
+    let config = Config::get()?;
+    build_registry(config.dev_mode)
+});
+
+handlebars_helper!(asset_helper: |url: str| assets::versioned_url(url));
+handlebars_helper!(pluralize_helper: |count: u64, singular: str, plural: str, { lang: str = "en" }| {
+    if is_plural(count, lang) { plural } else { singular }
+});
+
+/// `{{t "day.title" minutes=10}}` translates a key in the locale of the page, the hash fills in
+/// the `%{}` patterns of the translation
+struct TranslateHelper;
+
+impl HelperDef for TranslateHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc HandlebarsContext,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let key = h
+            .param(0)
+            .and_then(|param| param.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("t", 0))?;
+        let lang = get_lang(ctx);
+
+        let (patterns, values): (Vec<&str>, Vec<String>) = h
+            .hash()
+            .iter()
+            .map(|(name, value)| (*name, to_display_string(value.value())))
+            .unzip();
+        let translated = rust_i18n::replace_patterns(&t!(key, locale = lang), &patterns, &values);
+        Ok(ScopedJson::Derived(Value::String(translated)))
+    }
+}
+
+/// `{{format_time time}}` formats a date the way the locale of the page writes it, the
+/// `format` hash takes a strftime format instead
+struct FormatTimeHelper;
+
+impl HelperDef for FormatTimeHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc HandlebarsContext,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let time = h
+            .param(0)
+            .and_then(|param| param.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("format_time", 0))?;
+        let format = match h
+            .hash_get("format")
+            .and_then(|format| format.value().as_str())
+        {
+            Some(format) => format.to_string(),
+            None => t!("format.date_time", locale = get_lang(ctx)).into_owned(),
+        };
+        Ok(ScopedJson::Derived(Value::String(format_time(
+            time, &format,
+        ))))
+    }
+}
+
+/// `{{format_number points}}` groups the thousands and writes the decimals, as many as the
+/// `decimals` hash asks for, with the separators of the locale of the page
+struct FormatNumberHelper;
+
+impl HelperDef for FormatNumberHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc HandlebarsContext,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let value = h
+            .param(0)
+            .and_then(|param| param.value().as_f64())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("format_number", 0))?;
+        let decimals = h
+            .hash_get("decimals")
+            .and_then(|decimals| decimals.value().as_u64())
+            .unwrap_or(0) as usize;
+
+        let lang = get_lang(ctx);
+        let separators = NumberSeparators {
+            thousands: &t!("format.thousands_separator", locale = lang),
+            decimal: &t!("format.decimal_separator", locale = lang),
+        };
+        let formatted = format_number(value, decimals, &separators);
+        Ok(ScopedJson::Derived(Value::String(formatted)))
+    }
+}
+
+/// Locale of the page, set on the root of the data by [`render`]
+fn get_lang(ctx: &HandlebarsContext) -> &str {
+    ctx.data()
+        .get("lang")
+        .and_then(Value::as_str)
+        .unwrap_or_else(|| Locale::default().as_str())
+}
+
+fn to_display_string(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.to_owned(),
+        value => value.to_string(),
+    }
+}
+
+/// Compiles the views eagerly so that a broken template fails the startup instead of a request
+pub fn init() -> Result<()> {
+    registry().map(|_| ())
+}
+
+/// Renders a view in the locale of the request, which is available to the views as `lang`.
+/// A view named after the locale or one of its fallbacks, like `about_fr`, is picked over the
+/// default one.
+pub fn render<T: Serialize>(request: &HttpRequest, name: &str, data: &T) -> Result<String> {
+    let locale = http_helpers::get_user_locale(request)?;
+    let mut data = serde_json::to_value(data)?;
+    if let Value::Object(data) = &mut data {
+        // the forms of the views, the language switcher of the layout included, post the token
+        data.insert(
+            "csrfToken".to_string(),
+            http_helpers::get_csrf_token(request).into(),
+        );
+    }
+    render_localized(name, locale, &data)
+}
+
+/// Same as [`render`] for a locale that does not come from a request
+pub fn render_localized<T: Serialize>(name: &str, locale: Locale, data: &T) -> Result<String> {
+    let mut data = serde_json::to_value(data)?;
+    if let Value::Object(data) = &mut data {
+        data.entry("lang").or_insert_with(|| locale.as_str().into());
+        data.entry("locales")
+            .or_insert_with(|| get_switcher_locales(locale));
+    }
+
+    let registry = registry()?;
+    let localized_name = locale
+        .fallback_chain()
+        .into_iter()
+        .map(|code| format!("{name}_{code}"))
+        .find(|localized_name| registry.has_template(localized_name));
+    let name = localized_name.as_deref().unwrap_or(name);
+
+    let rendered = registry
+        .render(name, &data)
+        .with_context(|| format!("failed to render view {name}"))?;
+    Ok(rendered)
+}
+
+/// Languages offered by the switcher of the layout
+fn get_switcher_locales(current: Locale) -> Value {
+    Locale::all()
+        .into_iter()
+        .map(|locale| {
+            json!({
+                "code": locale.as_str(),
+                "name": locale.native_name(),
+                "selected": locale == current,
+            })
+        })
+        .collect()
+}
+
+fn registry() -> Result<&'static Handlebars<'static>> {
+    TEMPLATES
+        .as_ref()
+        .map_err(|e| anyhow!("failed to compile views: {e:#}"))
+}
+
+/// In dev mode, handlebars reads the template files again on each render so edits show up right away
+fn build_registry(dev_mode: bool) -> Result<Handlebars<'static>> {
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(true);
+    handlebars.set_dev_mode(dev_mode);
+    handlebars.register_helper("asset", Box::new(asset_helper));
+    handlebars.register_helper("format_number", Box::new(FormatNumberHelper));
+    handlebars.register_helper("format_time", Box::new(FormatTimeHelper));
+    handlebars.register_helper("pluralize", Box::new(pluralize_helper));
+    handlebars.register_helper("t", Box::new(TranslateHelper));
+
+    for path in embedded::list(VIEWS_DIR) {
+        let Some(name) = get_template_name(path) else {
+            continue;
+        };
+
+        debug!("registering view {name} from {path}");
+        if dev_mode {
+            handlebars.register_template_file(name, path)?;
+        } else {
+            handlebars.register_template_string(name, embedded::read_to_string(path)?)?;
+        }
+    }
+
+    Ok(handlebars)
+}
+
+/// Templates are named after their path relative to the views directory, like `partials/nav`
+fn get_template_name(path: &str) -> Option<&str> {
+    path.strip_prefix(VIEWS_DIR)?
+        .strip_prefix('/')?
+        .strip_suffix(".hbs")
+}
+
+/// Formats an RFC 3339 date, as serialized by chrono, values that are not dates are left untouched
+fn format_time(time: &str, format: &str) -> String {
+    match DateTime::parse_from_rfc3339(time) {
+        Ok(time) => time.format(format).to_string(),
+        Err(_) => time.to_string(),
+    }
+}
+
+struct NumberSeparators<'a> {
+    thousands: &'a str,
+    decimal: &'a str,
+}
+
+fn format_number(value: f64, decimals: usize, separators: &NumberSeparators) -> String {
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = match formatted.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push_str(separators.thousands);
+        }
+        grouped.push(digit);
+    }
+
+    let is_negative = value < 0.0 && formatted.chars().any(|c| ('1'..='9').contains(&c));
+    let sign = if is_negative { "-" } else { "" };
+    match fraction {
+        Some(fraction) => format!("{sign}{grouped}{}{fraction}", separators.decimal),
+        None => format!("{sign}{grouped}"),
+    }
+}
+
+/// French uses the singular for zero as well, regional variants follow their language
+fn is_plural(count: u64, lang: &str) -> bool {
+    match lang.split('-').next() {
+        Some("fr") => count > 1,
+        _ => count != 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    fn test_registry() -> Handlebars<'static> {
+        build_registry(false).unwrap()
+    }
+
+    fn french() -> Locale {
+        Locale::from_code("fr").unwrap()
+    }
+
+    #[test]
+    fn test_all_views_compile() {
+        let handlebars = test_registry();
+        for name in [
+            "index",
+            "day",
+            "layouts/base",
+            "partials/nav",
+            "partials/footer",
+        ] {
+            assert!(handlebars.has_template(name), "missing template {name}");
+        }
+    }
+
+    #[test]
+    fn test_get_template_name() {
+        assert_eq!(Some("index"), get_template_name("src/views/index.hbs"));
+        assert_eq!(
+            Some("partials/nav"),
+            get_template_name("src/views/partials/nav.hbs")
+        );
+        assert_eq!(None, get_template_name("src/views/notes.txt"));
+    }
+
+    #[test]
+    fn test_asset_helper() {
+        let handlebars = test_registry();
+        let rendered = handlebars
+            .render_template("{{asset \"/scripts/day.js\"}}", &json!({}))
+            .unwrap();
+        assert_eq!(assets::versioned_url("/scripts/day.js"), rendered);
+        assert_ne!("/scripts/day.js", rendered);
+    }
+
+    #[test]
+    fn test_format_time_helper() {
+        let handlebars = test_registry();
+        let template = "{{format_time time}}|{{format_time time format=\"%H:%M\"}}";
+        let render = |lang: &str| {
+            let data = json!({"time": "2025-12-03T07:05:09Z", "lang": lang});
+            handlebars.render_template(template, &data).unwrap()
+        };
+
+        assert_eq!("2025-12-03 07:05|07:05", render("en"));
+        assert_eq!("03/12/2025 07:05|07:05", render("fr"));
+    }
+
+    #[test]
+    fn test_format_number_helper() {
+        let handlebars = test_registry();
+        let render = |template: &str, lang: &str| {
+            let data = json!({"points": 1234567, "ratio": 1234.567, "lang": lang});
+            handlebars.render_template(template, &data).unwrap()
+        };
+
+        assert_eq!("1,234,567", render("{{format_number points}}", "en"));
+        assert_eq!(
+            "1\u{202f}234\u{202f}567",
+            render("{{format_number points}}", "fr")
+        );
+        assert_eq!(
+            "1,234.57",
+            render("{{format_number ratio decimals=2}}", "en")
+        );
+        assert_eq!(
+            "1\u{202f}234,6",
+            render("{{format_number ratio decimals=1}}", "fr")
+        );
+    }
+
+    #[test]
+    fn test_format_number() {
+        let separators = NumberSeparators {
+            thousands: ",",
+            decimal: ".",
+        };
+        assert_eq!("0", format_number(0.0, 0, &separators));
+        assert_eq!("999", format_number(999.0, 0, &separators));
+        assert_eq!("1,000", format_number(1000.0, 0, &separators));
+        assert_eq!("-12,345.6", format_number(-12345.64, 1, &separators));
+        assert_eq!("0.0", format_number(-0.01, 1, &separators));
+    }
+
+    #[test]
+    fn test_format_time_keeps_invalid_dates() {
+        assert_eq!("soon", format_time("soon", "%H:%M"));
+    }
+
+    #[test]
+    fn test_pluralize_helper() {
+        let handlebars = test_registry();
+        let template = "{{pluralize count \"guess\" \"guesses\" lang=lang}}";
+        let render = |count: u64, lang: &str| {
+            handlebars
+                .render_template(template, &json!({"count": count, "lang": lang}))
+                .unwrap()
+        };
+
+        assert_eq!("guesses", render(0, "en"));
+        assert_eq!("guess", render(1, "en"));
+        assert_eq!("guesses", render(2, "en"));
+        assert_eq!("guess", render(0, "fr"));
+        assert_eq!("guesses", render(2, "fr"));
+        assert_eq!("guess", render(0, "fr-CA"));
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_missing_variables() {
+        let handlebars = test_registry();
+        assert!(
+            handlebars
+                .render_template("{{missing}}", &json!({}))
+                .is_err()
+        );
+        assert!(
+            handlebars
+                .render_template("{{#if missing}}yes{{/if}}", &json!({}))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_layout_wraps_view() {
+        let data = json!({"catchphrase": "Lost <b>again</b>"});
+        let rendered = render_localized("404", french(), &data).unwrap();
+        assert!(rendered.starts_with("<!DOCTYPE html>\n<html lang=\"fr\">"));
+        assert!(rendered.contains("<title>AOT | Lost???</title>"));
+        assert!(rendered.contains("<body class=\"centered\">"));
+        assert!(rendered.contains("Lost <b>again</b>"));
+        assert!(rendered.contains("id=\"link-go-home\" href=\"/\""));
+    }
+
+    #[test]
+    fn test_layout_has_language_switcher() {
+        let rendered = render_localized("404", french(), &json!({"catchphrase": "Lost"})).unwrap();
+        assert!(rendered.contains("action=\"/locale\""));
+        assert!(rendered.contains("value=\"en\">English</button>"));
+        assert!(rendered.contains("value=\"fr\" disabled>Français</button>"));
+        assert!(!rendered.contains("name=\"_csrf\""));
+    }
+
+    #[test]
+    fn test_translate_helper() {
+        let handlebars = test_registry();
+        let render = |template: &str, lang: &str| {
+            handlebars
+                .render_template(template, &json!({"lang": lang, "minutes": 10}))
+                .unwrap()
+        };
+
+        assert_eq!("Leaderboard", render("{{t \"leaderboard.title\"}}", "en"));
+        assert_eq!("Classement", render("{{t \"leaderboard.title\"}}", "fr"));
+        assert_eq!(
+            "Within 10 min",
+            render("{{t \"leaderboard.close_guesses\" minutes=minutes}}", "en")
+        );
+        assert_eq!(
+            "[Leaderboard]",
+            render("[{{#if true}}{{t \"leaderboard.title\"}}{{/if}}]", "en")
+        );
+    }
+
+    /// Keys used with the `t` helper in the views, including those passed to other helpers
+    fn get_translation_keys_of_views() -> Vec<(String, String)> {
+        let key_regex = Regex::new(r#"[{(]t "([^"]+)""#).unwrap();
+        embedded::list(VIEWS_DIR)
+            .flat_map(|path| {
+                let content = embedded::read_to_string(path).unwrap();
+                key_regex
+                    .captures_iter(&content)
+                    .map(|captures| (path.to_string(), captures[1].to_string()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn find_translation<'a>(locale: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+        let mut parts = key.split('.');
+        let first = locale.get(parts.next()?)?;
+        parts.try_fold(first, |value, part| value.get(part))
+    }
+
+    /// Every translation file as written, without the fallbacks of rust-i18n
+    fn read_locale_files() -> Vec<(std::path::PathBuf, toml::Table)> {
+        std::fs::read_dir("locales")
+            .unwrap()
+            .map(|entry| {
+                let locale_path = entry.unwrap().path();
+                let locale = toml::from_str(&std::fs::read_to_string(&locale_path).unwrap());
+                (locale_path, locale.unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_every_locale_has_a_name_and_formats() {
+        for (locale_path, locale) in read_locale_files() {
+            for key in [
+                "locale.name",
+                "format.date_time",
+                "format.decimal_separator",
+                "format.thousands_separator",
+            ] {
+                let found = find_translation(&locale, key).is_some_and(toml::Value::is_str);
+                assert!(found, "{key} is missing from {locale_path:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_translation_keys_of_views_exist_in_every_locale() {
+        let keys = get_translation_keys_of_views();
+        assert!(keys.iter().any(|(_, key)| key == "day.joker_label"));
+
+        for (locale_path, locale) in read_locale_files() {
+            for (view, key) in keys.iter() {
+                let found = find_translation(&locale, key).is_some_and(toml::Value::is_str);
+                assert!(
+                    found,
+                    "{key} used in {view} is missing from {locale_path:?}"
+                );
+            }
+        }
+    }
+}
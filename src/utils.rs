@@ -1,18 +1,13 @@
 use anyhow::{Context, Result};
-use chrono::{DateTime, Datelike, FixedOffset, Timelike, Utc};
-use handlebars::Handlebars;
-use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
-use regex::Regex;
-use serde::Serialize;
-use std::cmp;
+use chrono::{DateTime, Datelike, FixedOffset, TimeZone, Timelike, Utc};
+use rand::{Rng, SeedableRng, distr::Alphanumeric, rngs::StdRng, seq::IndexedRandom};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
-use std::{fs, path::PathBuf};
 
 use crate::config::Config;
-use crate::database::user_repository::UserRepository;
+use crate::embedded;
 use crate::models::picture::Picture;
-use crate::models::user::User;
+use crate::scoring;
 
 pub type Day = u32;
 
@@ -36,10 +31,10 @@ pub fn capitalize(s: &str) -> String {
 pub fn generate_username(seed: u64) -> Result<String> {
     let mut rng = StdRng::seed_from_u64(seed);
 
-    let nouns = fs::read_to_string(DICO_NOUNS_PATH)?;
+    let nouns = embedded::read_to_string(DICO_NOUNS_PATH)?;
     let nouns = nouns.lines().collect::<Vec<_>>();
 
-    let adjectives = fs::read_to_string(DICO_ADJECTIVES_PATH)?;
+    let adjectives = embedded::read_to_string(DICO_ADJECTIVES_PATH)?;
     let adjectives = adjectives.lines().collect::<Vec<_>>();
 
     let rnd_noun = nouns
@@ -53,24 +48,13 @@ pub fn generate_username(seed: u64) -> Result<String> {
     Ok(username)
 }
 
-pub fn markdown_to_html(content: &str) -> Result<String> {
-    let link_regex = Regex::new(r"\[([^\]]+)\]\(([^)]+)\)")?;
-    let result = link_regex
-        .replace_all(content, r#"<a href="$2">$1</a>"#)
-        .to_string();
-
-    let bold_regex = Regex::new(r"\*\*(.*?)\*\*")?;
-    let result = bold_regex.replace_all(&result, "<b>$1</b>").to_string();
-
-    let italic_regex = Regex::new(r"\*(.*?)\*")?;
-    let result = italic_regex.replace_all(&result, "<i>$1</i>").to_string();
-
-    let code_regex = Regex::new(r"`(.*?)`")?;
-    let result = code_regex
-        .replace_all(&result, "<code>$1</code>")
-        .to_string();
-
-    Ok(result)
+/// Generates a random uppercase alphanumeric code, used for IDs and invite codes
+pub fn generate_code(length: usize) -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(|c| char::from(c).to_ascii_uppercase())
+        .collect()
 }
 
 pub fn is_day_valid(day: u32) -> bool {
@@ -108,29 +92,8 @@ pub fn is_day_valid(day: u32) -> bool {
 //     Ok((datetime.hour(), datetime.minute()))
 // }
 
-pub fn load_view(name: &str) -> Result<String> {
-    let view_path = PathBuf::from(format!("src/views/{}.html", name));
-    if view_path.exists() {
-        return Ok(fs::read_to_string(view_path)?);
-    }
-
-    let view_path = PathBuf::from(format!("src/views/{}.hbs", name));
-    Ok(fs::read_to_string(view_path)?)
-}
-
-pub fn render_view<T: Serialize>(name: &str, data: &T) -> Result<String> {
-    let raw_view = load_view(name)?;
-    let mut handlebars = Handlebars::new();
-    handlebars.register_template_string(name, raw_view)?;
-    let rendered = handlebars.render(name, &data)?;
-    Ok(rendered)
-}
-
-pub fn time_diff_to_points(diff_minutes: u32) -> u32 {
-    let config = Config::get().unwrap().score;
-    let ratio = diff_minutes as f64 / (config.divider as f64);
-    let result = config.max_reward * (1.0 - ratio.powf(config.exponent));
-    result.max(0.0) as u32 // Clamp negative points to zero
+pub fn time_diff_to_points(diff_minutes: u32) -> Result<u32> {
+    Ok(scoring::get_active_strategy()?.points(diff_minutes))
 }
 
 pub fn guess_order_to_bonus(order: u32) -> u32 {
@@ -151,16 +114,6 @@ pub fn guess_order_to_bonus(order: u32) -> u32 {
     bonus as u32
 }
 
-pub(crate) fn get_ranked_players_sorted() -> Result<Vec<User>> {
-    let mut ranked_users: Vec<_> = UserRepository::get_all_users()?
-        .iter()
-        .filter(|u| !u.hidden)
-        .cloned()
-        .collect();
-    ranked_users.sort_by_key(|u| cmp::Reverse(u.get_total_score().unwrap()));
-    Ok(ranked_users)
-}
-
 pub(crate) fn is_game_over() -> bool {
     get_days_remaining() < 0
 }
@@ -194,25 +147,46 @@ pub fn is_picture_released(utc_now: DateTime<Utc>, picture_day: Day) -> bool {
     }
 }
 
+/// Pictures are released and the game ends on CET time (+01:00)
+fn get_release_timezone() -> FixedOffset {
+    FixedOffset::east_opt(3600).unwrap()
+}
+
 fn is_time_after_6_am_cet(time: DateTime<Utc>) -> bool {
-    // Define CET timezone offset (+01:00)
-    let cet_offset = FixedOffset::east_opt(3600).unwrap();
-    let cet_now: DateTime<FixedOffset> = time.with_timezone(&cet_offset);
+    let cet_now: DateTime<FixedOffset> = time.with_timezone(&get_release_timezone());
     cet_now.hour() >= 6
 }
 
-pub fn compute_score(picture: &Picture, guess: (u32, u32)) -> Result<u32> {
+/// Time at which the picture of the given December day unlocks (6:00 a.m CET)
+pub fn get_picture_release_time(year: i32, picture_day: Day) -> Option<DateTime<Utc>> {
+    get_release_timezone()
+        .with_ymd_and_hms(year, 12, picture_day, 6, 0, 0)
+        .single()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Deadline of the edition, after which the scores are frozen (December 25th, 11:59 p.m CET)
+pub fn get_game_end_time(year: i32) -> Option<DateTime<Utc>> {
+    get_release_timezone()
+        .with_ymd_and_hms(year, 12, 25, 23, 59, 0)
+        .single()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+pub fn time_diff_minutes(picture: &Picture, guess: (u32, u32)) -> Result<u32> {
     let real_time_mins = picture.hours()? * 60 + picture.minutes()?;
     let guess_time_mins = guess.0 * 60 + guess.1;
-    let diff_mins = (real_time_mins).abs_diff(guess_time_mins);
-    let points = time_diff_to_points(diff_mins);
+    Ok((real_time_mins).abs_diff(guess_time_mins))
+}
+
+pub fn compute_score(picture: &Picture, guess: (u32, u32)) -> Result<u32> {
+    let diff_mins = time_diff_minutes(picture, guess)?;
+    let points = time_diff_to_points(diff_mins)?;
     Ok(points)
 }
 
 #[cfg(test)]
 mod tests {
-    use chrono::TimeZone;
-
     use super::*;
 
     #[test]
@@ -269,24 +243,36 @@ mod tests {
         assert!(!is_picture_released(utc_time, day))
     }
 
+    #[test]
+    fn test_get_picture_release_time_is_5_am_utc() {
+        let expected = Utc.with_ymd_and_hms(2025, 12, 15, 5, 0, 0).unwrap();
+        assert_eq!(Some(expected), get_picture_release_time(2025, 15))
+    }
+
+    #[test]
+    fn test_get_game_end_time_is_10_59_pm_utc() {
+        let expected = Utc.with_ymd_and_hms(2025, 12, 25, 22, 59, 0).unwrap();
+        assert_eq!(Some(expected), get_game_end_time(2025))
+    }
+
     #[test]
     fn test_time_diff_to_points_perfect_gives_max_reward() {
         let config = Config::get().unwrap().score;
-        assert_eq!(config.max_reward as u32, time_diff_to_points(0))
+        assert_eq!(config.max_reward as u32, time_diff_to_points(0).unwrap())
     }
 
     #[test]
     fn test_time_diff_to_points_worst_gives_nothing() {
-        assert_eq!(0, time_diff_to_points(24 * 60))
+        assert_eq!(0, time_diff_to_points(24 * 60).unwrap())
     }
 
     #[test]
     fn test_time_diff_to_points_avg_gives_ok_reward() {
-        assert_eq!(81, time_diff_to_points(12 * 60))
+        assert_eq!(81, time_diff_to_points(12 * 60).unwrap())
     }
 
     #[test]
     fn test_time_diff_to_points_about_section() {
-        assert_eq!(178, time_diff_to_points(75))
+        assert_eq!(178, time_diff_to_points(75).unwrap())
     }
 }